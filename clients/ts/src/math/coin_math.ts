@@ -1,4 +1,4 @@
-import { MAX_COIN_SUPPLY, MAX_VIRTUAL_COIN_RESERVES, VIRTUAL_SOL_REVERSES } from "./constants";
+import { BASIS_POINTS, CurveParams, DEFAULT_CURVE_PARAMS } from "./constants";
 import { ceil_div } from "./math";
 
 enum Side {
@@ -6,34 +6,68 @@ enum Side {
   Sell,
 }
 
-export function buy(remaining_coin_supply: bigint, amount: bigint): bigint {
-  return calc(remaining_coin_supply, amount, Side.Buy);
+export function buy(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  return calc(remaining_coin_supply, amount, Side.Buy, params);
 }
 
-export function sell(remaining_coin_supply: bigint, amount: bigint): bigint {
-  return calc(remaining_coin_supply, amount, Side.Sell);
+export function sell(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  return calc(remaining_coin_supply, amount, Side.Sell, params);
 }
 
-function calc(remaining_coin_supply: bigint, amount: bigint, side: Side): bigint {
-  let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
+export const SPOT_PRICE_SCALE = BigInt(1e12);
 
-  let numerator = already_sold * VIRTUAL_SOL_REVERSES;
-  let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold;
+export function spot_price(remaining_coin_supply: bigint, params: CurveParams = DEFAULT_CURVE_PARAMS): bigint {
+  let max_virtual_coin_reserves = params.max_coin_supply + params.virtual_coin_reserves;
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let virtual_coin_reserves = max_virtual_coin_reserves - already_sold;
+  let virtual_sol_reserves = (params.virtual_sol_reverses * max_virtual_coin_reserves) / virtual_coin_reserves;
+  return (virtual_sol_reserves * SPOT_PRICE_SCALE) / virtual_coin_reserves;
+}
+
+/** The fully diluted market cap in lamports, all the max coin supply valued at a `spot_price` of any curve. */
+export function market_cap(spot_price: bigint, params: CurveParams = DEFAULT_CURVE_PARAMS): bigint {
+  return (spot_price * params.max_coin_supply) / SPOT_PRICE_SCALE;
+}
+
+/** The progress of the bonding curve toward completion in basis points, rounded down. */
+export function progress_bps(remaining_coin_supply: bigint, params: CurveParams = DEFAULT_CURVE_PARAMS): bigint {
+  let sellable_coins = params.max_coin_supply - params.reverse_coins;
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  if (already_sold > sellable_coins) {
+    already_sold = sellable_coins;
+  }
+  return (already_sold * BASIS_POINTS) / sellable_coins;
+}
+
+function calc(remaining_coin_supply: bigint, amount: bigint, side: Side, params: CurveParams): bigint {
+  let max_virtual_coin_reserves = params.max_coin_supply + params.virtual_coin_reserves;
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+
+  let numerator = already_sold * params.virtual_sol_reverses;
+  let denominator = max_virtual_coin_reserves - already_sold;
 
   if (side == Side.Buy) {
     let sol_before = numerator / denominator;
 
     let already_sold_target = already_sold + amount;
-    numerator = already_sold_target * VIRTUAL_SOL_REVERSES;
-    denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold_target;
+    numerator = already_sold_target * params.virtual_sol_reverses;
+    denominator = max_virtual_coin_reserves - already_sold_target;
     let sol_after = ceil_div(numerator, denominator);
     return sol_after - sol_before;
   } else {
     let sol_before = ceil_div(numerator, denominator);
 
     let already_sold_target = already_sold - amount;
-    numerator = already_sold_target * VIRTUAL_SOL_REVERSES;
-    denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold_target;
+    numerator = already_sold_target * params.virtual_sol_reverses;
+    denominator = max_virtual_coin_reserves - already_sold_target;
     let sol_after = numerator / denominator;
     if (sol_before <= sol_after) {
       return 0n;
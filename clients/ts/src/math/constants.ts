@@ -1,7 +1,58 @@
 export const FEE_RATE_BASIS_POINT = BigInt(1e8);
+export const BASIS_POINTS = 10000n;
+export const DECIMALS = 6;
+export const MAX_DECIMALS = 9;
 export const MAX_COIN_SUPPLY = BigInt(10e8) * BigInt(1e6);
 export const VIRTUAL_SOL_REVERSES = BigInt("30000000000");
 export const VIRTUAL_COIN_RESERVES = BigInt("73000000000000");
 export const MAX_VIRTUAL_COIN_RESERVES = MAX_COIN_SUPPLY + VIRTUAL_COIN_RESERVES;
 export const REVERSE_COINS = BigInt("206900000000000");
 export const SELLABLE_COINS = MAX_COIN_SUPPLY - REVERSE_COINS;
+export const LINEAR_OFFSET = BigInt("280000000000000");
+export const LINEAR_SCALE = BigInt(1e19);
+export const EXPONENTIAL_DOUBLING_COINS = BigInt("200000000000000");
+export const EXPONENTIAL_SOL_SCALE = BigInt(6e9);
+
+/** The curve params of a config, the defaults are the constants above. */
+export type CurveParams = {
+  max_coin_supply: bigint;
+  reverse_coins: bigint;
+  virtual_coin_reserves: bigint;
+  virtual_sol_reverses: bigint;
+  decimals: number;
+  linear_offset: bigint;
+  linear_scale: bigint;
+  exponential_doubling_coins: bigint;
+  exponential_sol_scale: bigint;
+};
+
+export const DEFAULT_CURVE_PARAMS: CurveParams = {
+  max_coin_supply: MAX_COIN_SUPPLY,
+  reverse_coins: REVERSE_COINS,
+  virtual_coin_reserves: VIRTUAL_COIN_RESERVES,
+  virtual_sol_reverses: VIRTUAL_SOL_REVERSES,
+  decimals: DECIMALS,
+  linear_offset: LINEAR_OFFSET,
+  linear_scale: LINEAR_SCALE,
+  exponential_doubling_coins: EXPONENTIAL_DOUBLING_COINS,
+  exponential_sol_scale: EXPONENTIAL_SOL_SCALE,
+};
+
+/** Returns the params of a coin with `decimals` decimals, as rescaled by the program at create. */
+export function with_decimals(params: CurveParams, decimals: number): CurveParams {
+  let rescale = (coins: bigint) =>
+    decimals >= params.decimals
+      ? coins * 10n ** BigInt(decimals - params.decimals)
+      : coins / 10n ** BigInt(params.decimals - decimals);
+  return {
+    max_coin_supply: rescale(params.max_coin_supply),
+    reverse_coins: rescale(params.reverse_coins),
+    virtual_coin_reserves: rescale(params.virtual_coin_reserves),
+    virtual_sol_reverses: params.virtual_sol_reverses,
+    decimals,
+    linear_offset: rescale(params.linear_offset),
+    linear_scale: rescale(rescale(params.linear_scale)),
+    exponential_doubling_coins: rescale(params.exponential_doubling_coins),
+    exponential_sol_scale: params.exponential_sol_scale,
+  };
+}
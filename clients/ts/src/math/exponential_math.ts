@@ -0,0 +1,49 @@
+import { CurveParams, DEFAULT_CURVE_PARAMS } from "./constants";
+import { SPOT_PRICE_SCALE } from "./coin_math";
+import { ceil_div } from "./math";
+
+export const ONE = BigInt(1e18);
+export const LN_2 = BigInt("693147180559945309");
+
+export function exp2(y: bigint): bigint {
+  let z = ((y % ONE) * LN_2) / ONE;
+  let sum = ONE;
+  let term = ONE;
+  for (let n = 1n; term > 0n; n++) {
+    term = (term * z) / (n * ONE);
+    sum += term;
+  }
+  return sum << (y / ONE);
+}
+
+function sol_reserves(already_sold: bigint, params: CurveParams): bigint {
+  return params.exponential_sol_scale * (exp2((already_sold * ONE) / params.exponential_doubling_coins) - ONE);
+}
+
+export function buy(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let sol_before = sol_reserves(already_sold, params) / ONE;
+  let sol_after = ceil_div(sol_reserves(already_sold + amount, params), ONE);
+  return sol_after - sol_before;
+}
+
+export function sell(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let sol_before = ceil_div(sol_reserves(already_sold, params), ONE);
+  let sol_after = sol_reserves(already_sold - amount, params) / ONE;
+  return sol_before <= sol_after ? 0n : sol_before - sol_after;
+}
+
+export function spot_price(remaining_coin_supply: bigint, params: CurveParams = DEFAULT_CURVE_PARAMS): bigint {
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let price = (params.exponential_sol_scale * exp2((already_sold * ONE) / params.exponential_doubling_coins)) / ONE;
+  return (((price * LN_2) / ONE) * SPOT_PRICE_SCALE) / params.exponential_doubling_coins;
+}
@@ -7,3 +7,18 @@ export function buy_fee(pay_amount_without_fee: bigint, maker_fee_rate: bigint):
 export function sell_fee(sol_amount: bigint, taker_fee_rate: bigint): bigint {
   return (sol_amount * taker_fee_rate) / FEE_RATE_BASIS_POINT;
 }
+
+export function buy_pay_amount(total_pay: bigint, maker_fee_rate: bigint): bigint {
+  let pay_amount = (total_pay * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT + maker_fee_rate);
+  if (pay_amount < total_pay && pay_amount + 1n + buy_fee(pay_amount + 1n, maker_fee_rate) <= total_pay) {
+    return pay_amount + 1n;
+  }
+  return pay_amount;
+}
+
+export function launch_tax_rate(launch_tax_rate: bigint, launch_tax_slots: bigint, elapsed_slots: bigint): bigint {
+  if (elapsed_slots >= launch_tax_slots) {
+    return 0n;
+  }
+  return (launch_tax_rate * (launch_tax_slots - elapsed_slots)) / launch_tax_slots;
+}
@@ -0,0 +1,34 @@
+import { CurveParams, DEFAULT_CURVE_PARAMS } from "./constants";
+import { SPOT_PRICE_SCALE } from "./coin_math";
+import { ceil_div } from "./math";
+
+function sol_reserves(already_sold: bigint, params: CurveParams): bigint {
+  return already_sold * (already_sold + params.linear_offset);
+}
+
+export function buy(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let sol_before = sol_reserves(already_sold, params) / params.linear_scale;
+  let sol_after = ceil_div(sol_reserves(already_sold + amount, params), params.linear_scale);
+  return sol_after - sol_before;
+}
+
+export function sell(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let sol_before = ceil_div(sol_reserves(already_sold, params), params.linear_scale);
+  let sol_after = sol_reserves(already_sold - amount, params) / params.linear_scale;
+  return sol_before <= sol_after ? 0n : sol_before - sol_after;
+}
+
+export function spot_price(remaining_coin_supply: bigint, params: CurveParams = DEFAULT_CURVE_PARAMS): bigint {
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  return ((2n * already_sold + params.linear_offset) * SPOT_PRICE_SCALE) / params.linear_scale;
+}
@@ -0,0 +1,6 @@
+export function swap(reserve_in: bigint, reserve_out: bigint, amount_in: bigint): bigint {
+  if (amount_in == 0n) {
+    return 0n;
+  }
+  return (reserve_out * amount_in) / (reserve_in + amount_in);
+}
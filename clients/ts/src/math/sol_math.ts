@@ -1,28 +1,38 @@
-import { MAX_COIN_SUPPLY, MAX_VIRTUAL_COIN_RESERVES, SELLABLE_COINS, VIRTUAL_SOL_REVERSES } from "./constants";
+import { CurveParams, DEFAULT_CURVE_PARAMS } from "./constants";
 
-export function buy_exact_in(remaining_coin_supply: bigint, amount: bigint): bigint {
-  let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
-  let numerator = already_sold * VIRTUAL_SOL_REVERSES;
-  let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold;
+export function buy_exact_in(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  let max_virtual_coin_reserves = params.max_coin_supply + params.virtual_coin_reserves;
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let numerator = already_sold * params.virtual_sol_reverses;
+  let denominator = max_virtual_coin_reserves - already_sold;
   let sol_before = numerator / denominator;
 
   let sol_after = sol_before + amount;
-  numerator = sol_after * MAX_VIRTUAL_COIN_RESERVES;
-  denominator = VIRTUAL_SOL_REVERSES + sol_after;
+  numerator = sol_after * max_virtual_coin_reserves;
+  denominator = params.virtual_sol_reverses + sol_after;
   let coin_after = numerator / denominator;
   if (coin_after <= already_sold) {
     return 0n;
-  } else if (coin_after >= SELLABLE_COINS) {
-    return SELLABLE_COINS - already_sold;
+  } else if (coin_after >= params.max_coin_supply - params.reverse_coins) {
+    return params.max_coin_supply - params.reverse_coins - already_sold;
   } else {
     return coin_after - already_sold;
   }
 }
 
-export function sell_exact_out(remaining_coin_supply: bigint, amount: bigint): bigint {
-  let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
-  let numerator = already_sold * VIRTUAL_SOL_REVERSES;
-  let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold;
+export function sell_exact_out(
+  remaining_coin_supply: bigint,
+  amount: bigint,
+  params: CurveParams = DEFAULT_CURVE_PARAMS
+): bigint {
+  let max_virtual_coin_reserves = params.max_coin_supply + params.virtual_coin_reserves;
+  let already_sold = params.max_coin_supply - remaining_coin_supply;
+  let numerator = already_sold * params.virtual_sol_reverses;
+  let denominator = max_virtual_coin_reserves - already_sold;
   let sol_before = numerator / denominator;
 
   if (sol_before < amount) {
@@ -33,8 +43,8 @@ export function sell_exact_out(remaining_coin_supply: bigint, amount: bigint): b
   }
 
   let sol_after = sol_before - amount;
-  numerator = sol_after * MAX_VIRTUAL_COIN_RESERVES;
-  denominator = VIRTUAL_SOL_REVERSES + sol_after;
+  numerator = sol_after * max_virtual_coin_reserves;
+  denominator = params.virtual_sol_reverses + sol_after;
   let coin_after = numerator / denominator;
   if (coin_after >= already_sold) {
     throw new Error("Unexpect exact output");
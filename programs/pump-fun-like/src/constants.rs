@@ -2,17 +2,21 @@
 /// Fee amount = amount * fee_rate / FEE_RATE_BASIS_POINT.
 pub const FEE_RATE_BASIS_POINT: u32 = 1e8 as u32;
 
+/// The basis point of ratios, e.g. 100 basis points is 1%.
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// The default decimals of a coin, in which the curve constants are written.
 pub const DECIMALS: u8 = 6;
-pub const MAX_COIN_SUPPLY: u64 = (10e8 * 1e6) as u64;
-pub const REVERSE_COINS: u64 = 2_0690_0000 * 1e6 as u64;
-pub const SELLABLE_COINS: u64 = MAX_COIN_SUPPLY - REVERSE_COINS;
+/// The max decimals of a coin.
+pub const MAX_DECIMALS: u8 = 9;
 
 pub const COIN_SEED: &str = "coin";
 pub const SOL_VAULT_SEED: &str = "coin_sol_vault";
+pub const POOL_SEED: &str = "pool";
+pub const ORDER_SEED: &str = "order";
+pub const DCA_SEED: &str = "dca";
+pub const USER_STATE_SEED: &str = "user_state";
+pub const USER_POSITION_SEED: &str = "user_position";
 
 pub const SYMBOL_MIN_LEN: usize = 2;
 pub const SYMBOL_MAX_LEN: usize = 10;
-
-pub const VIRTUAL_COIN_RESERVES: u64 = 7300_0000e6 as u64;
-pub const MAX_VIRTUAL_COIN_RESERVES: u64 = MAX_COIN_SUPPLY + VIRTUAL_COIN_RESERVES;
-pub const VIRTUAL_SOL_REVERSES: u64 = 30e9 as u64;
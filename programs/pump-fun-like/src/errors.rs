@@ -50,4 +50,103 @@ pub enum Error {
     /// code = 6015
     #[msg("Invalid receive")]
     InvalidReceive,
+    /// code = 6016
+    #[msg("Migration authority mismatch")]
+    MigrationAuthorityMismatch,
+    /// code = 6017
+    #[msg("Bonding curve not completed")]
+    CurveNotCompleted,
+    /// code = 6018
+    #[msg("Already migrated")]
+    AlreadyMigrated,
+    /// code = 6019
+    #[msg("Coin account mismatch")]
+    CoinAccountMismatch,
+    /// code = 6020
+    #[msg("Coin halted")]
+    CoinHalted,
+    /// code = 6021
+    #[msg("Invalid status transition")]
+    InvalidStatusTransition,
+    /// code = 6022
+    #[msg("Invalid creator buy limit")]
+    InvalidCreatorBuyLimit,
+    /// code = 6023
+    #[msg("Creator buy limit exceeded")]
+    CreatorBuyLimitExceeded,
+    /// code = 6024
+    #[msg("Identical coins")]
+    IdenticalCoins,
+    /// code = 6025
+    #[msg("Trade expired")]
+    TradeExpired,
+    /// code = 6026
+    #[msg("Invalid order amount")]
+    InvalidOrderAmount,
+    /// code = 6027
+    #[msg("Order owner mismatch")]
+    OrderOwnerMismatch,
+    /// code = 6028
+    #[msg("Order not triggered")]
+    OrderNotTriggered,
+    /// code = 6029
+    #[msg("Invalid DCA arguments")]
+    InvalidDcaArgs,
+    /// code = 6030
+    #[msg("DCA slice not ready")]
+    DcaSliceNotReady,
+    /// code = 6031
+    #[msg("DCA owner mismatch")]
+    DcaOwnerMismatch,
+    /// code = 6032
+    #[msg("Invalid sell fraction")]
+    InvalidSellFraction,
+    /// code = 6033
+    #[msg("Invalid wallet buy limit")]
+    InvalidWalletBuyLimit,
+    /// code = 6034
+    #[msg("Wallet buy limit exceeded")]
+    WalletBuyLimitExceeded,
+    /// code = 6035
+    #[msg("Invalid launch tax rate")]
+    InvalidLaunchTaxRate,
+    /// code = 6036
+    #[msg("Invalid allowlist")]
+    InvalidAllowlist,
+    /// code = 6037
+    #[msg("Allowlist proof required")]
+    AllowlistProofRequired,
+    /// code = 6038
+    #[msg("Invalid allowlist proof")]
+    InvalidAllowlistProof,
+    /// code = 6039
+    #[msg("Allowlist allocation exceeded")]
+    AllowlistAllocationExceeded,
+    /// code = 6040
+    #[msg("User position mismatch")]
+    UserPositionMismatch,
+    /// code = 6041
+    #[msg("Invalid cooldown sell tax rate")]
+    InvalidCooldownSellTaxRate,
+    /// code = 6042
+    #[msg("Sell cooldown active")]
+    SellCooldownActive,
+    /// code = 6043
+    #[msg("Price impact exceeded")]
+    PriceImpactExceeded,
+    /// code = 6044
+    #[msg("Invalid curve params")]
+    InvalidCurveParams,
+    /// code = 6045
+    #[msg("Math overflow")]
+    MathOverflow,
+    /// code = 6046
+    #[msg("Math underflow")]
+    MathUnderflow,
+    /// code = 6047
+    #[msg("Invalid decimals")]
+    InvalidDecimals,
+    /// code = 6048
+    #[msg("Insufficient sol vault balance")]
+    InsufficientSolVaultBalance,
 }
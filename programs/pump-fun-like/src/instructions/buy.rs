@@ -1,6 +1,7 @@
-use crate::{errors::Error, state::*};
+use crate::{constants::USER_STATE_SEED, errors::Error, state::*};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 #[derive(Accounts)]
 pub struct Buy<'info> {
@@ -10,12 +11,19 @@ pub struct Buy<'info> {
         has_one = config @ Error::ConfigAccountMismatch,
         has_one = coin_vault @ Error::CoinVaultMismatch,
         has_one = sol_vault @ Error::SolVaultAccountMismatch,
+        has_one = coin_mint @ Error::CoinMintAccountMismatch,
     )]
     pub coin: Box<Account<'info, Coin>>,
-    #[account(mut,
-        constraint = coin_recipient.mint == coin.coin_mint.key() @ Error::CoinMintAccountMismatch
+    pub coin_mint: Box<Account<'info, Mint>>,
+    /// The associated token account of the recipient, created if it does not exist.
+    #[account(init_if_needed,
+        payer = payer,
+        associated_token::mint = coin_mint,
+        associated_token::authority = recipient,
     )]
     pub coin_recipient: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used as the owner of the coin recipient.
+    pub recipient: UncheckedAccount<'info>,
     /// CHECK: This account is only used to receive the fee.
     #[account(mut)]
     pub fee_recipient: UncheckedAccount<'info>,
@@ -24,34 +32,79 @@ pub struct Buy<'info> {
     /// CHECK: This account is only used to receive the sol.
     #[account(mut)]
     pub sol_vault: UncheckedAccount<'info>,
+    /// Records the coins bought by the recipient.
+    #[account(init_if_needed,
+        payer = payer,
+        space = UserState::LEN,
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), recipient.key().as_ref()],
+        bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+    /// Optional position of the payer, updated with the trade.
+    #[account(mut,
+        has_one = coin @ Error::UserPositionMismatch,
+        constraint = user_position.owner == payer.key() @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize)]
 pub struct BuyArgs {
     /// Amount of coin to buy.
     pub amount: u64,
     /// Maximum amount of SOL to pay.
     pub max_pay: u64,
+    /// Optional expiry of the trade, omitted by older clients.
+    pub expiry: Option<Expiry>,
+    /// Proof that the payer is on the allowlist, required while the allowlist window is open.
+    pub allowlist_proof: Option<AllowlistProof>,
+}
+
+impl AnchorDeserialize for BuyArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            amount: u64::deserialize_reader(reader)?,
+            max_pay: u64::deserialize_reader(reader)?,
+            expiry: Expiry::deserialize_trailing(reader)?,
+            allowlist_proof: AllowlistProof::deserialize_trailing(reader)?,
+        })
+    }
 }
 
 pub fn handler(ctx: Context<Buy>, args: BuyArgs) -> Result<()> {
+    Expiry::require_not_expired(args.expiry)?;
+    let allocation = ctx
+        .accounts
+        .coin
+        .check_allowlist(&ctx.accounts.recipient.key(), args.allowlist_proof.as_ref())?;
+
     let coin_copy = ctx.accounts.coin.clone();
-    ctx.accounts.coin.buy(
+    let trade = ctx.accounts.coin.buy(
         &ctx.accounts.config,
         &coin_copy.to_account_info(),
         &ctx.accounts.coin_vault,
         &ctx.accounts.sol_vault,
         &ctx.accounts.fee_recipient,
-        &ctx.accounts.coin_recipient,
+        &ctx.accounts.coin_recipient.to_account_info(),
         &ctx.accounts.payer,
         &ctx.accounts.token_program,
         &ctx.accounts.system_program,
         args.amount,
         args.max_pay,
-    )
+        ctx.accounts.user_position.as_deref_mut().map(|p| &mut **p),
+    )?;
+    ctx.accounts.user_state.record_buy(
+        &ctx.accounts.config,
+        ctx.accounts.recipient.key(),
+        &ctx.accounts.coin,
+        ctx.bumps.user_state,
+        trade.coin_amount,
+    )?;
+    ctx.accounts.user_state.check_allocation(allocation)
 }
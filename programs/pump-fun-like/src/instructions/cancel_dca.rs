@@ -0,0 +1,19 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelDca<'info> {
+    /// The escrowed sol and the rent are refunded to the owner.
+    #[account(mut,
+        close = owner,
+        has_one = owner @ Error::DcaOwnerMismatch,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<CancelDca>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    pub coin: Box<Account<'info, Coin>>,
+    /// The escrowed sol and the rent are refunded to the owner.
+    #[account(mut,
+        close = owner,
+        has_one = coin @ Error::CoinAccountMismatch,
+        has_one = owner @ Error::OrderOwnerMismatch,
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = order,
+    )]
+    pub order_coin_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = owner,
+    )]
+    pub owner_coin_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<CancelOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let order_info = order.to_account_info();
+
+    // 1. refund the escrowed coins
+    let escrowed_coins = ctx.accounts.order_coin_vault.amount;
+    if escrowed_coins > 0 {
+        order.release_coins(
+            &order_info,
+            &ctx.accounts.order_coin_vault,
+            &ctx.accounts.owner_coin_account.to_account_info(),
+            &ctx.accounts.token_program,
+            escrowed_coins,
+        )?;
+    }
+
+    // 2. close the coin vault of the order
+    order.close_coin_vault(
+        &order_info,
+        &ctx.accounts.order_coin_vault,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.token_program,
+    )
+}
@@ -0,0 +1,19 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    /// The rent is refunded to the owner.
+    #[account(mut,
+        close = owner,
+        has_one = owner @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<ClosePosition>) -> Result<()> {
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::constants::*;
 use crate::errors::Error;
+use crate::math::curve::CurveKind;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
@@ -11,9 +12,10 @@ use anchor_spl::{
 };
 
 #[derive(Accounts)]
+#[instruction(args: CreateArgs)]
 pub struct Create<'info> {
     #[account(has_one = fee_recipient @ Error::FeeRecipientMismatch)]
-    pub config: Account<'info, Config>,
+    pub config: Box<Account<'info, Config>>,
 
     #[account(init, payer = payer, space = Coin::LEN,
         seeds = [
@@ -22,10 +24,10 @@ pub struct Create<'info> {
         ],
         bump
     )]
-    pub coin: Account<'info, Coin>,
+    pub coin: Box<Account<'info, Coin>>,
     #[account(init, payer = payer,
         mint::authority = coin,
-        mint::decimals = DECIMALS,
+        mint::decimals = args.decimals.unwrap_or(DECIMALS),
     )]
     pub coin_mint: Box<Account<'info, Mint>>,
     #[account(init, payer = payer,
@@ -52,11 +54,33 @@ pub struct Create<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize)]
 pub struct CreateArgs {
     pub name: String,
     pub symbol: String,
     pub uri: String,
+    /// Optional merkle root of the wallets allowed to buy during the allowlist window, omitted by older clients.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Length of the allowlist window in slots, after which the public can trade, omitted by older clients.
+    pub allowlist_slots: u64,
+    /// The family of the bonding curve of the coin. Defaults to [CurveKind::ConstantProduct], omitted by older clients.
+    pub curve_kind: CurveKind,
+    /// The decimals of the coin, at most [MAX_DECIMALS]. Defaults to [DECIMALS], omitted by older clients.
+    pub decimals: Option<u8>,
+}
+
+impl AnchorDeserialize for CreateArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            name: String::deserialize_reader(reader)?,
+            symbol: String::deserialize_reader(reader)?,
+            uri: String::deserialize_reader(reader)?,
+            allowlist_root: deserialize_trailing_option(reader)?,
+            allowlist_slots: deserialize_trailing_or_default(reader)?,
+            curve_kind: deserialize_trailing_or_default(reader)?,
+            decimals: deserialize_trailing_option(reader)?,
+        })
+    }
 }
 
 pub fn handler<'a, 'b, 'c, 'info>(
@@ -78,13 +102,20 @@ pub fn handler<'a, 'b, 'c, 'info>(
         &ctx.accounts.coin_vault,
         args.symbol.clone(),
         ctx.bumps.coin,
-    );
+        Clock::get()?.slot,
+        args.curve_kind,
+    )?;
+    if let Some(root) = args.allowlist_root {
+        ctx.accounts
+            .coin
+            .initialize_allowlist(root, args.allowlist_slots)?;
+    }
 
     // 3. Create the SOL vault.
     ctx.accounts.coin.create_sol_vault(
         &ctx.accounts.payer,
         &ctx.accounts.sol_vault,
-        &ctx.program_id,
+        ctx.program_id,
         &ctx.accounts.system_program,
         &ctx.accounts.rent,
     )?;
@@ -0,0 +1,112 @@
+use crate::constants::{USER_POSITION_SEED, USER_STATE_SEED};
+use crate::errors::Error;
+use crate::instructions::buy_exact_in::BuyExactInArgs;
+use crate::instructions::create::{self, *};
+use crate::state::{Expiry, UserPosition, UserState};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token;
+
+#[derive(Accounts)]
+pub struct CreateAndBuy<'info> {
+    pub create: Create<'info>,
+    /// CHECK: The associated token account of the payer, created by the handler.
+    #[account(mut)]
+    pub coin_recipient: UncheckedAccount<'info>,
+    /// Records the coins bought by the creator.
+    #[account(init,
+        payer = create.payer,
+        space = UserState::LEN,
+        seeds = [USER_STATE_SEED.as_bytes(), create.coin.key().as_ref(), create.payer.key().as_ref()],
+        bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+    /// Optional position of the creator, opened with the coin and updated with the buy.
+    #[account(init,
+        payer = create.payer,
+        space = UserPosition::LEN,
+        seeds = [USER_POSITION_SEED.as_bytes(), create.coin.key().as_ref(), create.payer.key().as_ref()],
+        bump,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateAndBuyArgs {
+    pub create: CreateArgs,
+    pub buy: BuyExactInArgs,
+}
+
+pub fn handler<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CreateAndBuy<'info>>,
+    args: CreateAndBuyArgs,
+) -> Result<()> {
+    Expiry::require_not_expired(args.buy.expiry)?;
+    // The allowlist of the coin does not restrict its creator, a proof is not taken.
+    require!(
+        args.buy.allowlist_proof.is_none(),
+        Error::InvalidAllowlistProof
+    );
+
+    // 1. Create the coin.
+    create::handler(
+        Context::new(
+            ctx.program_id,
+            &mut ctx.accounts.create,
+            ctx.remaining_accounts,
+            ctx.bumps.create,
+        ),
+        args.create,
+    )?;
+
+    // 2. Create the coin recipient of the creator.
+    let accounts = &mut ctx.accounts.create;
+    associated_token::create(CpiContext::new(
+        accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: accounts.payer.to_account_info(),
+            associated_token: ctx.accounts.coin_recipient.to_account_info(),
+            authority: accounts.payer.to_account_info(),
+            mint: accounts.coin_mint.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    // 3. Buy the coin for the creator. `check_allowlist` is skipped, the creator opens the allowlist window
+    // and buys in the same transaction, before any wallet on the allowlist can.
+    if let (Some(position), Some(bump)) = (
+        ctx.accounts.user_position.as_deref_mut(),
+        ctx.bumps.user_position,
+    ) {
+        position.initialize(accounts.payer.key(), accounts.coin.key(), bump);
+    }
+    let coin_copy = accounts.coin.clone();
+    let trade = accounts.coin.buy_exact_in(
+        &accounts.config,
+        &coin_copy.to_account_info(),
+        &accounts.coin_vault,
+        &accounts.sol_vault,
+        &accounts.fee_recipient,
+        &ctx.accounts.coin_recipient.to_account_info(),
+        &accounts.payer,
+        &accounts.token_program,
+        &accounts.system_program,
+        args.buy.pay_amount,
+        args.buy.fee_included,
+        args.buy.min_receive,
+        ctx.accounts.user_position.as_deref_mut().map(|p| &mut **p),
+    )?;
+    accounts.config.check_creator_buy(
+        trade.coin_amount,
+        accounts.coin.curve_params.max_coin_supply,
+    )?;
+    ctx.accounts.user_state.record_buy(
+        &accounts.config,
+        accounts.payer.key(),
+        &accounts.coin,
+        ctx.bumps.user_state,
+        trade.coin_amount,
+    )
+}
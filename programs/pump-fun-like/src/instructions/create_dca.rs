@@ -0,0 +1,82 @@
+use crate::constants::DCA_SEED;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(args: CreateDcaArgs)]
+pub struct CreateDca<'info> {
+    #[account(
+        has_one = coin_mint @ Error::CoinMintAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    pub coin_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        payer = owner,
+        space = Dca::LEN,
+        seeds = [DCA_SEED.as_bytes(), coin.key().as_ref(), owner.key().as_ref(), &args.id.to_le_bytes()],
+        bump,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    /// Receives the coins of every slice.
+    #[account(init_if_needed,
+        payer = owner,
+        associated_token::mint = coin_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_coin_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateDcaArgs {
+    /// Chosen by the owner to tell apart the DCA accounts on the same coin.
+    pub id: u64,
+    /// Amount of sol to pay per slice, not including the fee.
+    pub pay_amount: u64,
+    /// Minimum amount of coin to receive per slice.
+    pub min_receive: u64,
+    /// Seconds between two slices, must be positive.
+    pub interval: i64,
+    /// Amount of sol to escrow for all the slices, including the fees.
+    pub deposit: u64,
+}
+
+pub fn handler(ctx: Context<CreateDca>, args: CreateDcaArgs) -> Result<()> {
+    require!(
+        args.pay_amount > 0 && args.interval > 0 && args.deposit > 0,
+        Error::InvalidDcaArgs
+    );
+    ctx.accounts.coin.require_trading()?;
+
+    ctx.accounts.dca.initialize(
+        ctx.accounts.owner.key(),
+        ctx.accounts.coin.key(),
+        args.id,
+        args.pay_amount,
+        args.min_receive,
+        args.interval,
+        args.deposit,
+        Clock::get()?.unix_timestamp,
+        ctx.bumps.dca,
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.dca.to_account_info(),
+            },
+        ),
+        args.deposit,
+    )
+}
@@ -0,0 +1,40 @@
+use crate::constants::POOL_SEED;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreatePool<'info> {
+    #[account(has_one = migration_authority @ Error::MigrationAuthorityMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = sol_vault @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    #[account(init, payer = migration_authority, space = Pool::LEN,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            coin.coin_mint.as_ref()
+        ],
+        bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    /// CHECK: This account is only used to read the sol.
+    pub sol_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub migration_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreatePool>) -> Result<()> {
+    ctx.accounts.coin.mark_migrated()?;
+    ctx.accounts.pool.initialize(
+        &ctx.accounts.coin,
+        &ctx.accounts.sol_vault,
+        &ctx.accounts.rent,
+        ctx.bumps.pool,
+    )
+}
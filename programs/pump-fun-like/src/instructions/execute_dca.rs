@@ -0,0 +1,133 @@
+use crate::constants::USER_STATE_SEED;
+use crate::errors::Error;
+use crate::math::fee_math;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ExecuteDca<'info> {
+    #[account(has_one = fee_recipient @ Error::FeeRecipientMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = coin_vault @ Error::CoinVaultMismatch,
+        has_one = sol_vault @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    /// CHECK: This account is only used to receive the fee.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub coin_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to receive the sol.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        has_one = coin @ Error::CoinAccountMismatch,
+        has_one = owner @ Error::DcaOwnerMismatch,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    /// CHECK: This account only receives the refund once the DCA is finished.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = owner,
+    )]
+    pub owner_coin_account: Box<Account<'info, TokenAccount>>,
+    /// Records the coins bought by the owner.
+    #[account(init_if_needed,
+        payer = cranker,
+        space = UserState::LEN,
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+    /// Optional position of the owner, updated with each slice.
+    #[account(mut,
+        has_one = coin @ Error::UserPositionMismatch,
+        has_one = owner @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
+    /// Pays each slice with the escrowed sol.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ExecuteDca>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    // The coin can no longer be bought once it graduates, the escrow is refunded.
+    if accounts.coin.is_graduated() {
+        return accounts.dca.close(accounts.owner.to_account_info());
+    }
+
+    // The coin can only be bought with a proof during its allowlist window.
+    accounts.coin.check_allowlist(&accounts.owner.key(), None)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= accounts.dca.next_slice_at, Error::DcaSliceNotReady);
+
+    // 1. hand the sol of the slice to the cranker, who pays the curve
+    let buy_fee_rate = accounts.coin.buy_fee_rate(&accounts.config)?;
+    let pay_amount = accounts.dca.slice_pay_amount(buy_fee_rate)?;
+    let slice = pay_amount
+        .checked_add(fee_math::buy(pay_amount, buy_fee_rate)?)
+        .ok_or(Error::MathOverflow)?;
+    accounts.dca.sub_lamports(slice)?;
+    accounts.cranker.add_lamports(slice)?;
+
+    // 2. buy the coins for the owner
+    let coin_copy = accounts.coin.clone();
+    let trade = accounts.coin.buy_exact_in(
+        &accounts.config,
+        &coin_copy.to_account_info(),
+        &accounts.coin_vault,
+        &accounts.sol_vault,
+        &accounts.fee_recipient,
+        &accounts.owner_coin_account.to_account_info(),
+        &accounts.cranker,
+        &accounts.token_program,
+        &accounts.system_program,
+        pay_amount,
+        false,
+        accounts.dca.slice_min_receive(pay_amount),
+        accounts.user_position.as_deref_mut().map(|p| &mut **p),
+    )?;
+
+    // 3. return the sol left by the end of the curve to the escrow
+    let spent = trade
+        .sol_amount
+        .checked_add(trade.fee)
+        .ok_or(Error::MathOverflow)?;
+    if slice > spent {
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.cranker.to_account_info(),
+                    to: accounts.dca.to_account_info(),
+                },
+            ),
+            slice - spent,
+        )?;
+    }
+    accounts.dca.record_slice(spent, now)?;
+    accounts.user_state.record_buy(
+        &accounts.config,
+        accounts.owner.key(),
+        &accounts.coin,
+        ctx.bumps.user_state,
+        trade.coin_amount,
+    )?;
+
+    if accounts.coin.is_graduated() || accounts.dca.is_finished(buy_fee_rate)? {
+        accounts.dca.close(accounts.owner.to_account_info())?;
+    }
+    Ok(())
+}
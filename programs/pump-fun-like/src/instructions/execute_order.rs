@@ -0,0 +1,179 @@
+use crate::constants::USER_STATE_SEED;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ExecuteOrder<'info> {
+    #[account(has_one = fee_recipient @ Error::FeeRecipientMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = coin_vault @ Error::CoinVaultMismatch,
+        has_one = sol_vault @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    /// CHECK: This account is only used to receive the fee.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub coin_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to receive or send the sol.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+    /// The rent and the unspent sol are refunded to the owner.
+    #[account(mut,
+        close = owner,
+        has_one = coin @ Error::CoinAccountMismatch,
+        has_one = owner @ Error::OrderOwnerMismatch,
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = order,
+    )]
+    pub order_coin_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to receive the sol.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = owner,
+    )]
+    pub owner_coin_account: Box<Account<'info, TokenAccount>>,
+    /// Records the coins bought by the owner with a buy order, and holds the sell cooldown of a sell order.
+    #[account(init_if_needed,
+        payer = keeper,
+        space = UserState::LEN,
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+    /// Optional position of the owner, updated with the fill.
+    #[account(mut,
+        has_one = coin @ Error::UserPositionMismatch,
+        has_one = owner @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
+    /// Fills the order with the escrow, and receives the keeper reward.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = keeper,
+    )]
+    pub keeper_coin_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ExecuteOrder>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let order = &accounts.order;
+    let order_info = order.to_account_info();
+    let quote = order.quote(&accounts.config, &accounts.coin)?;
+    require!(order.is_triggered(quote), Error::OrderNotTriggered);
+
+    let coin_copy = accounts.coin.clone();
+    match order.side {
+        OrderSide::Buy => {
+            // The coin can only be bought with a proof during its allowlist window.
+            coin_copy.check_allowlist(&order.owner, None)?;
+
+            // 1. hand the escrowed sol and the keeper reward to the keeper, who pays the curve
+            let escrow_sol = order
+                .amount
+                .checked_add(order.keeper_reward)
+                .ok_or(Error::MathOverflow)?;
+            order_info.sub_lamports(escrow_sol)?;
+            accounts.keeper.add_lamports(escrow_sol)?;
+
+            // 2. buy the coins for the owner
+            let pay_amount = order.pay_amount(coin_copy.buy_fee_rate(&accounts.config)?)?;
+            let trade = accounts.coin.buy_exact_in(
+                &accounts.config,
+                &coin_copy.to_account_info(),
+                &accounts.coin_vault,
+                &accounts.sol_vault,
+                &accounts.fee_recipient,
+                &accounts.owner_coin_account.to_account_info(),
+                &accounts.keeper,
+                &accounts.token_program,
+                &accounts.system_program,
+                pay_amount,
+                false,
+                order.min_receive,
+                accounts.user_position.as_deref_mut().map(|p| &mut **p),
+            )?;
+            accounts.user_state.record_buy(
+                &accounts.config,
+                order.owner,
+                &accounts.coin,
+                ctx.bumps.user_state,
+                trade.coin_amount,
+            )?;
+
+            // 3. refund the sol left by rounding or by the end of the curve
+            let unspent = order
+                .amount
+                .checked_sub(trade.sol_amount)
+                .and_then(|amount| amount.checked_sub(trade.fee))
+                .ok_or(Error::MathUnderflow)?;
+            if unspent > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: accounts.keeper.to_account_info(),
+                            to: accounts.owner.to_account_info(),
+                        },
+                    ),
+                    unspent,
+                )?;
+            }
+        }
+        OrderSide::Sell => {
+            // 1. hand the escrowed coins to the keeper, who sells them to the curve
+            order.release_coins(
+                &order_info,
+                &accounts.order_coin_vault,
+                &accounts.keeper_coin_account.to_account_info(),
+                &accounts.token_program,
+                order.amount,
+            )?;
+
+            // 2. sell the coins for the owner, who is still subject to the cooldown after a buy
+            let cooldown_tax_rate = accounts.user_state.cooldown_tax_rate(&accounts.config)?;
+            accounts.coin.sell(
+                &accounts.config,
+                &accounts.coin_vault,
+                &accounts.sol_vault,
+                &accounts.fee_recipient,
+                &accounts.owner.to_account_info(),
+                &accounts.keeper_coin_account,
+                &accounts.keeper,
+                &accounts.token_program,
+                &accounts.system_program,
+                order.amount,
+                order.min_receive,
+                cooldown_tax_rate,
+                accounts.user_position.as_deref_mut().map(|p| &mut **p),
+            )?;
+
+            // 3. pay the keeper reward
+            order_info.sub_lamports(order.keeper_reward)?;
+            accounts.keeper.add_lamports(order.keeper_reward)?;
+        }
+    }
+
+    // 4. close the coin vault of the order
+    order.close_coin_vault(
+        &order_info,
+        &accounts.order_coin_vault,
+        &accounts.owner.to_account_info(),
+        &accounts.token_program,
+    )
+}
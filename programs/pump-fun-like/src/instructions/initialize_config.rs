@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+use crate::math::curve::CurveParams;
 use crate::Config;
 
 #[derive(Accounts)]
@@ -25,12 +26,17 @@ pub struct InitializeConfigArgs {
     pub taker_fee_rate: u32,
     /// The fee rate for maker.
     pub maker_fee_rate: u32,
+    /// The parameters of the bonding curve of the coins created with the config.
+    pub curve_params: CurveParams,
 }
 
 pub fn handler(ctx: Context<InitializeConfig>, args: InitializeConfigArgs) -> Result<()> {
-    ctx.accounts
-        .config
-        .initialize(args.authority, args.fee_recipient, args.migration_authority);
+    ctx.accounts.config.initialize(
+        args.authority,
+        args.fee_recipient,
+        args.migration_authority,
+        args.curve_params,
+    )?;
     ctx.accounts.config.update_fee(
         args.create_coin_fee,
         args.taker_fee_rate,
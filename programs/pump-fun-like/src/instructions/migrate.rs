@@ -0,0 +1,45 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(has_one = migration_authority @ Error::MigrationAuthorityMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = coin_vault @ Error::CoinVaultMismatch,
+        has_one = sol_vault @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    #[account(mut)]
+    pub coin_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to send the sol.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = migration_authority,
+    )]
+    pub coin_recipient: Box<Account<'info, TokenAccount>>,
+    /// Receives the sol of the bonding curve.
+    #[account(mut)]
+    pub migration_authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Migrate>) -> Result<()> {
+    let coin_copy = ctx.accounts.coin.clone();
+    ctx.accounts.coin.migrate(
+        &coin_copy.to_account_info(),
+        &ctx.accounts.coin_vault,
+        &ctx.accounts.sol_vault,
+        &ctx.accounts.coin_recipient,
+        &ctx.accounts.migration_authority,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+    )
+}
@@ -2,16 +2,60 @@
 
 pub mod buy;
 pub mod buy_exact_in;
+pub mod cancel_dca;
+pub mod cancel_order;
+pub mod close_position;
 pub mod create;
+pub mod create_and_buy;
+pub mod create_dca;
+pub mod create_pool;
+pub mod execute_dca;
+pub mod execute_order;
 pub mod initialize_config;
+pub mod migrate;
+pub mod open_position;
+pub mod place_order;
+pub mod pool_swap;
+pub mod quote_buy;
+pub mod quote_buy_exact_in;
+pub mod quote_sell;
+pub mod quote_sell_exact_out;
+pub mod realloc_coin;
+pub mod realloc_config;
 pub mod sell;
 pub mod sell_exact_out;
+pub mod sell_fraction;
+pub mod set_halted;
+pub mod swap_coins;
+pub mod update_config;
 pub mod update_fee;
 
 pub use buy::*;
 pub use buy_exact_in::*;
+pub use cancel_dca::*;
+pub use cancel_order::*;
+pub use close_position::*;
 pub use create::*;
+pub use create_and_buy::*;
+pub use create_dca::*;
+pub use create_pool::*;
+pub use execute_dca::*;
+pub use execute_order::*;
 pub use initialize_config::*;
+pub use migrate::*;
+pub use open_position::*;
+pub use place_order::*;
+pub use pool_swap::*;
+pub use quote_buy::*;
+pub use quote_buy_exact_in::*;
+pub use quote_sell::*;
+pub use quote_sell_exact_out::*;
+pub use realloc_coin::*;
+pub use realloc_config::*;
 pub use sell::*;
 pub use sell_exact_out::*;
+pub use sell_fraction::*;
+pub use set_halted::*;
+pub use swap_coins::*;
+pub use update_config::*;
 pub use update_fee::*;
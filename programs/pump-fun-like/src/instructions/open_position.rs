@@ -0,0 +1,28 @@
+use crate::constants::USER_POSITION_SEED;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    pub coin: Box<Account<'info, Coin>>,
+    #[account(init,
+        payer = owner,
+        space = UserPosition::LEN,
+        seeds = [USER_POSITION_SEED.as_bytes(), coin.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<OpenPosition>) -> Result<()> {
+    ctx.accounts.user_position.initialize(
+        ctx.accounts.owner.key(),
+        ctx.accounts.coin.key(),
+        ctx.bumps.user_position,
+    );
+    Ok(())
+}
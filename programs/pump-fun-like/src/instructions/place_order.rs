@@ -0,0 +1,113 @@
+use crate::constants::ORDER_SEED;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(args: PlaceOrderArgs)]
+pub struct PlaceOrder<'info> {
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = coin_mint @ Error::CoinMintAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    pub coin_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        payer = owner,
+        space = Order::LEN,
+        seeds = [ORDER_SEED.as_bytes(), coin.key().as_ref(), owner.key().as_ref(), &args.id.to_le_bytes()],
+        bump,
+    )]
+    pub order: Box<Account<'info, Order>>,
+    /// Escrows the coins of a sell order.
+    #[account(init,
+        payer = owner,
+        associated_token::mint = coin_mint,
+        associated_token::authority = order,
+    )]
+    pub order_coin_vault: Box<Account<'info, TokenAccount>>,
+    /// Pays the coins of a sell order, and receives the coins of a buy order.
+    #[account(init_if_needed,
+        payer = owner,
+        associated_token::mint = coin_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_coin_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PlaceOrderArgs {
+    /// Chosen by the owner to tell apart the orders on the same coin.
+    pub id: u64,
+    pub side: OrderSide,
+    pub kind: OrderKind,
+    /// Buy: amount of sol to spend, including the fee. Sell: amount of coin to sell.
+    pub amount: u64,
+    /// Buy: amount of coin to receive. Sell: amount of sol to receive, not including the fee.
+    pub trigger_amount: u64,
+    /// Minimum amount to receive when the order is filled, in the same unit as `trigger_amount`.
+    pub min_receive: u64,
+}
+
+pub fn handler(ctx: Context<PlaceOrder>, args: PlaceOrderArgs) -> Result<()> {
+    require!(args.amount > 0, Error::InvalidOrderAmount);
+    ctx.accounts.coin.require_trading()?;
+
+    let keeper_reward = ctx.accounts.config.order_keeper_reward;
+    ctx.accounts.order.initialize(
+        ctx.accounts.owner.key(),
+        ctx.accounts.coin.key(),
+        args.id,
+        args.side,
+        args.kind,
+        args.amount,
+        args.trigger_amount,
+        args.min_receive,
+        keeper_reward,
+        ctx.bumps.order,
+    );
+
+    // 1. escrow the coins of a sell order
+    let escrow_sol = match args.side {
+        OrderSide::Buy => args
+            .amount
+            .checked_add(keeper_reward)
+            .ok_or(Error::MathOverflow)?,
+        OrderSide::Sell => {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.owner_coin_account.to_account_info(),
+                        to: ctx.accounts.order_coin_vault.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                args.amount,
+            )?;
+            keeper_reward
+        }
+    };
+
+    // 2. escrow the sol of a buy order and the keeper reward
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.order.to_account_info(),
+            },
+        ),
+        escrow_sol,
+    )
+}
@@ -0,0 +1,99 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct PoolSwap<'info> {
+    #[account(has_one = fee_recipient @ Error::FeeRecipientMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = coin_vault @ Error::CoinVaultMismatch,
+        has_one = sol_vault @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    #[account(mut, has_one = coin @ Error::CoinAccountMismatch)]
+    pub pool: Box<Account<'info, Pool>>,
+    /// CHECK: This account is only used to receive the fee.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub coin_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to receive and send the sol.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = payer,
+    )]
+    pub coin_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PoolSwapSide {
+    /// Pay SOL to receive coin.
+    Buy,
+    /// Pay coin to receive SOL.
+    Sell,
+}
+
+#[derive(AnchorSerialize)]
+pub struct PoolSwapArgs {
+    pub side: PoolSwapSide,
+    /// Amount of SOL to pay (not including the fee) when buying, or amount of coin to sell when selling.
+    pub amount_in: u64,
+    /// Minimum amount of coin to receive when buying, or minimum amount of SOL to receive when selling.
+    pub min_receive: u64,
+    /// Optional expiry of the swap, omitted by older clients.
+    pub expiry: Option<Expiry>,
+}
+
+impl AnchorDeserialize for PoolSwapArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            side: PoolSwapSide::deserialize_reader(reader)?,
+            amount_in: u64::deserialize_reader(reader)?,
+            min_receive: u64::deserialize_reader(reader)?,
+            expiry: Expiry::deserialize_trailing(reader)?,
+        })
+    }
+}
+
+pub fn handler(ctx: Context<PoolSwap>, args: PoolSwapArgs) -> Result<()> {
+    Expiry::require_not_expired(args.expiry)?;
+
+    match args.side {
+        PoolSwapSide::Buy => ctx.accounts.pool.buy(
+            &ctx.accounts.config,
+            &ctx.accounts.coin,
+            &ctx.accounts.coin_vault,
+            &ctx.accounts.sol_vault,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.coin_account,
+            &ctx.accounts.payer,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            args.amount_in,
+            args.min_receive,
+        ),
+        PoolSwapSide::Sell => ctx.accounts.pool.sell(
+            &ctx.accounts.config,
+            &ctx.accounts.coin,
+            &ctx.accounts.coin_vault,
+            &ctx.accounts.sol_vault,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.coin_account,
+            &ctx.accounts.payer,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            args.amount_in,
+            args.min_receive,
+        ),
+    }
+}
@@ -0,0 +1,22 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QuoteBuy<'info> {
+    pub config: Box<Account<'info, Config>>,
+    #[account(has_one = config @ Error::ConfigAccountMismatch)]
+    pub coin: Box<Account<'info, Coin>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct QuoteBuyArgs {
+    /// Amount of coin to buy.
+    pub amount: u64,
+}
+
+pub fn handler(ctx: Context<QuoteBuy>, args: QuoteBuyArgs) -> Result<Quote> {
+    ctx.accounts
+        .coin
+        .quote_buy(&ctx.accounts.config, args.amount)
+}
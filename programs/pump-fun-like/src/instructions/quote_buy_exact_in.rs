@@ -0,0 +1,33 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QuoteBuyExactIn<'info> {
+    pub config: Box<Account<'info, Config>>,
+    #[account(has_one = config @ Error::ConfigAccountMismatch)]
+    pub coin: Box<Account<'info, Coin>>,
+}
+
+#[derive(AnchorSerialize)]
+pub struct QuoteBuyExactInArgs {
+    /// Amount of SOL to pay, not including the fee unless `fee_included` is set.
+    pub pay_amount: u64,
+    /// Whether the `pay_amount` includes the maker fee, so it is the total spent. Omitted by older clients.
+    pub fee_included: bool,
+}
+
+impl AnchorDeserialize for QuoteBuyExactInArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            pay_amount: u64::deserialize_reader(reader)?,
+            fee_included: deserialize_trailing_or_default(reader)?,
+        })
+    }
+}
+
+pub fn handler(ctx: Context<QuoteBuyExactIn>, args: QuoteBuyExactInArgs) -> Result<Quote> {
+    ctx.accounts
+        .coin
+        .quote_buy_exact_in(&ctx.accounts.config, args.pay_amount, args.fee_included)
+}
@@ -0,0 +1,33 @@
+use crate::constants::USER_STATE_SEED;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QuoteSell<'info> {
+    pub config: Box<Account<'info, Config>>,
+    #[account(has_one = config @ Error::ConfigAccountMismatch)]
+    pub coin: Box<Account<'info, Coin>>,
+    /// CHECK: The user state of the seller, only read for the sell cooldown if it exists.
+    #[account(
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), seller.key().as_ref()],
+        bump,
+    )]
+    pub user_state: UncheckedAccount<'info>,
+    /// CHECK: This account is only used to derive the user state.
+    pub seller: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct QuoteSellArgs {
+    /// Amount of coin to sell.
+    pub amount: u64,
+}
+
+pub fn handler(ctx: Context<QuoteSell>, args: QuoteSellArgs) -> Result<Quote> {
+    let cooldown_tax_rate =
+        UserState::cooldown_sell_tax_rate(&ctx.accounts.config, &ctx.accounts.user_state)?;
+    ctx.accounts
+        .coin
+        .quote_sell(&ctx.accounts.config, args.amount, cooldown_tax_rate)
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+use anchor_spl::token::Mint;
+
+use crate::errors::Error;
+use crate::math::curve::CurveParams;
+use crate::state::{Coin, Config};
+
+#[derive(Accounts)]
+pub struct ReallocCoin<'info> {
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: a coin created before its layout grew does not deserialize, it is checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub coin: UncheckedAccount<'info>,
+    pub coin_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ReallocCoin>) -> Result<()> {
+    let coin_info = ctx.accounts.coin.to_account_info();
+    {
+        let data = coin_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 + 32 * 2 && data[..8] == Coin::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            data[8..8 + 32] == ctx.accounts.config.key().to_bytes(),
+            Error::ConfigAccountMismatch
+        );
+        require!(
+            data[8 + 32..8 + 32 * 2] == ctx.accounts.coin_mint.key().to_bytes(),
+            Error::CoinMintAccountMismatch
+        );
+    }
+
+    if coin_info.data_len() < Coin::LEN {
+        let rent = Rent::get()?;
+        let lamports = rent
+            .minimum_balance(Coin::LEN)
+            .saturating_sub(coin_info.lamports());
+        if lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: coin_info.clone(),
+                    },
+                ),
+                lamports,
+            )?;
+        }
+        coin_info.realloc(Coin::LEN, true)?;
+    }
+
+    let mut data = coin_info.try_borrow_mut_data()?;
+    let mut coin = Coin::try_deserialize(&mut &data[..])?;
+    // A coin created before the curve params were stored on it was priced with the legacy params,
+    // whatever its config holds now.
+    let legacy_params = CurveParams::LEGACY.with_decimals(ctx.accounts.coin_mint.decimals)?;
+    if coin.curve_params == CurveParams::default() {
+        coin.curve_params = legacy_params;
+    }
+    // A coin created before the params of each curve were stored on it was priced with the legacy params of its curve.
+    if coin.curve_params.validate_kind(coin.curve_kind).is_err() {
+        coin.curve_params.linear_offset = legacy_params.linear_offset;
+        coin.curve_params.linear_scale = legacy_params.linear_scale;
+        coin.curve_params.exponential_doubling_coins = legacy_params.exponential_doubling_coins;
+        coin.curve_params.exponential_sol_scale = legacy_params.exponential_sol_scale;
+        coin.curve_params.validate_kind(coin.curve_kind)?;
+    }
+    coin.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+use crate::errors::Error;
+use crate::math::curve::CurveParams;
+use crate::state::Config;
+
+#[derive(Accounts)]
+pub struct ReallocConfig<'info> {
+    /// CHECK: a config created before its layout grew does not deserialize, it is checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ReallocConfigArgs {
+    /// The parameters of the bonding curve to set on the config.
+    /// Required for a config created before the parameters were stored on it.
+    pub curve_params: Option<CurveParams>,
+}
+
+pub fn handler(ctx: Context<ReallocConfig>, args: ReallocConfigArgs) -> Result<()> {
+    let config_info = ctx.accounts.config.to_account_info();
+    {
+        let data = config_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 + 32 && data[..8] == Config::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            data[8..8 + 32] == ctx.accounts.authority.key().to_bytes(),
+            Error::AuthorityMismatch
+        );
+    }
+
+    if config_info.data_len() < Config::LEN {
+        let rent = Rent::get()?;
+        let lamports = rent
+            .minimum_balance(Config::LEN)
+            .saturating_sub(config_info.lamports());
+        if lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: config_info.clone(),
+                    },
+                ),
+                lamports,
+            )?;
+        }
+        config_info.realloc(Config::LEN, true)?;
+    }
+
+    let mut data = config_info.try_borrow_mut_data()?;
+    let mut config = Config::try_deserialize(&mut &data[..])?;
+    if let Some(curve_params) = args.curve_params {
+        config.curve_params = curve_params;
+    }
+    config.curve_params.validate()?;
+    config.try_serialize(&mut &mut data[..])
+}
@@ -1,3 +1,4 @@
+use crate::constants::USER_STATE_SEED;
 use crate::errors::Error;
 use crate::state::*;
 use anchor_lang::prelude::*;
@@ -28,6 +29,18 @@ pub struct Sell<'info> {
         token::authority = payer,
     )]
     pub coin_payer: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The user state of the payer, only read for the sell cooldown if it exists.
+    #[account(
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub user_state: UncheckedAccount<'info>,
+    /// Optional position of the payer, updated with the trade.
+    #[account(mut,
+        has_one = coin @ Error::UserPositionMismatch,
+        constraint = user_position.owner == payer.key() @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -35,15 +48,31 @@ pub struct Sell<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize)]
 pub struct SellArgs {
     /// Amount of coin to sell.
     pub amount: u64,
     /// Minimum amount of SOL to receive.
     pub min_receive: u64,
+    /// Optional expiry of the trade, omitted by older clients.
+    pub expiry: Option<Expiry>,
+}
+
+impl AnchorDeserialize for SellArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            amount: u64::deserialize_reader(reader)?,
+            min_receive: u64::deserialize_reader(reader)?,
+            expiry: Expiry::deserialize_trailing(reader)?,
+        })
+    }
 }
 
 pub fn handler(ctx: Context<Sell>, args: SellArgs) -> Result<()> {
+    Expiry::require_not_expired(args.expiry)?;
+
+    let cooldown_tax_rate =
+        UserState::cooldown_sell_tax_rate(&ctx.accounts.config, &ctx.accounts.user_state)?;
     ctx.accounts.coin.sell(
         &ctx.accounts.config,
         &ctx.accounts.coin_vault,
@@ -56,5 +85,8 @@ pub fn handler(ctx: Context<Sell>, args: SellArgs) -> Result<()> {
         &ctx.accounts.system_program,
         args.amount,
         args.min_receive,
-    )
+        cooldown_tax_rate,
+        ctx.accounts.user_position.as_deref_mut().map(|p| &mut **p),
+    )?;
+    Ok(())
 }
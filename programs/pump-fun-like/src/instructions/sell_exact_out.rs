@@ -1,8 +1,9 @@
+use crate::constants::USER_STATE_SEED;
 use crate::errors::Error;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 
-use crate::{Coin, Config};
+use crate::{Coin, Config, Expiry, UserPosition, UserState};
 
 #[derive(Accounts)]
 pub struct SellExactOut<'info> {
@@ -29,6 +30,18 @@ pub struct SellExactOut<'info> {
         token::authority = payer,
     )]
     pub coin_payer: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The user state of the payer, only read for the sell cooldown if it exists.
+    #[account(
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub user_state: UncheckedAccount<'info>,
+    /// Optional position of the payer, updated with the trade.
+    #[account(mut,
+        has_one = coin @ Error::UserPositionMismatch,
+        constraint = user_position.owner == payer.key() @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -36,15 +49,31 @@ pub struct SellExactOut<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize)]
 pub struct SellExactOutArgs {
     /// Amount of sol to receive.
     pub receive: u64,
     /// Maximum amount of coin to sell.
     pub max_pay: u64,
+    /// Optional expiry of the trade, omitted by older clients.
+    pub expiry: Option<Expiry>,
+}
+
+impl AnchorDeserialize for SellExactOutArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            receive: u64::deserialize_reader(reader)?,
+            max_pay: u64::deserialize_reader(reader)?,
+            expiry: Expiry::deserialize_trailing(reader)?,
+        })
+    }
 }
 
 pub fn handler(ctx: Context<SellExactOut>, args: SellExactOutArgs) -> Result<()> {
+    Expiry::require_not_expired(args.expiry)?;
+
+    let cooldown_tax_rate =
+        UserState::cooldown_sell_tax_rate(&ctx.accounts.config, &ctx.accounts.user_state)?;
     ctx.accounts.coin.sell_exact_out(
         &ctx.accounts.config,
         &ctx.accounts.coin_vault,
@@ -57,5 +86,8 @@ pub fn handler(ctx: Context<SellExactOut>, args: SellExactOutArgs) -> Result<()>
         &ctx.accounts.system_program,
         args.receive,
         args.max_pay,
-    )
+        cooldown_tax_rate,
+        ctx.accounts.user_position.as_deref_mut().map(|p| &mut **p),
+    )?;
+    Ok(())
 }
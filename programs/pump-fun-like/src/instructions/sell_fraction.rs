@@ -0,0 +1,104 @@
+use crate::constants::{BASIS_POINTS, USER_STATE_SEED};
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct SellFraction<'info> {
+    #[account(has_one = fee_recipient @ Error::FeeRecipientMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = coin_vault @ Error::CoinVaultMismatch,
+        has_one = sol_vault @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin: Box<Account<'info, Coin>>,
+    /// CHECK: This account is only used to receive the fee.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub coin_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to send the sol.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+    /// CHECK: This account is only used to receive the sol.
+    #[account(mut)]
+    pub sol_recipient: UncheckedAccount<'info>,
+    #[account(mut,
+        token::mint = coin.coin_mint,
+        token::authority = payer,
+    )]
+    pub coin_payer: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The user state of the payer, only read for the sell cooldown if it exists.
+    #[account(
+        seeds = [USER_STATE_SEED.as_bytes(), coin.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub user_state: UncheckedAccount<'info>,
+    /// Optional position of the payer, updated with the trade.
+    #[account(mut,
+        has_one = coin @ Error::UserPositionMismatch,
+        constraint = user_position.owner == payer.key() @ Error::UserPositionMismatch,
+    )]
+    pub user_position: Option<Box<Account<'info, UserPosition>>>,
+    /// Receives the rent of the coin payer if it is closed.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SellFractionArgs {
+    /// The share of the coin payer balance to sell, in basis points.
+    pub fraction_bps: u16,
+    /// Minimum amount of SOL to receive.
+    pub min_receive: u64,
+    /// Whether to close the coin payer once it is emptied.
+    pub close_if_empty: bool,
+    /// Optional expiry of the trade.
+    pub expiry: Option<Expiry>,
+}
+
+pub fn handler(ctx: Context<SellFraction>, args: SellFractionArgs) -> Result<()> {
+    Expiry::require_not_expired(args.expiry)?;
+    require!(
+        args.fraction_bps > 0 && args.fraction_bps <= BASIS_POINTS,
+        Error::InvalidSellFraction
+    );
+
+    let amount = (ctx.accounts.coin_payer.amount as u128 * args.fraction_bps as u128
+        / BASIS_POINTS as u128) as u64;
+    let cooldown_tax_rate =
+        UserState::cooldown_sell_tax_rate(&ctx.accounts.config, &ctx.accounts.user_state)?;
+    ctx.accounts.coin.sell(
+        &ctx.accounts.config,
+        &ctx.accounts.coin_vault,
+        &ctx.accounts.sol_vault,
+        &ctx.accounts.fee_recipient,
+        &ctx.accounts.sol_recipient,
+        &ctx.accounts.coin_payer,
+        &ctx.accounts.payer,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        amount,
+        args.min_receive,
+        cooldown_tax_rate,
+        ctx.accounts.user_position.as_deref_mut().map(|p| &mut **p),
+    )?;
+
+    ctx.accounts.coin_payer.reload()?;
+    if args.close_if_empty && ctx.accounts.coin_payer.amount == 0 {
+        token::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.coin_payer.to_account_info(),
+                destination: ctx.accounts.payer.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ))?;
+    }
+    Ok(())
+}
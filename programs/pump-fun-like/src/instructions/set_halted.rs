@@ -0,0 +1,22 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetHalted<'info> {
+    #[account(has_one = authority @ Error::AuthorityMismatch)]
+    pub config: Account<'info, Config>,
+    #[account(mut, has_one = config @ Error::ConfigAccountMismatch)]
+    pub coin: Account<'info, Coin>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetHaltedArgs {
+    /// Whether to halt or resume the trading on the bonding curve.
+    pub halted: bool,
+}
+
+pub fn handler(ctx: Context<SetHalted>, args: SetHaltedArgs) -> Result<()> {
+    ctx.accounts.coin.set_halted(args.halted)
+}
@@ -0,0 +1,162 @@
+use crate::constants::USER_STATE_SEED;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct SwapCoins<'info> {
+    #[account(has_one = fee_recipient @ Error::FeeRecipientMismatch)]
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: This account is only used to receive the fee.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    /// The coin to sell.
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        constraint = coin_a.coin_vault == coin_a_vault.key() @ Error::CoinVaultMismatch,
+        constraint = coin_a.sol_vault == coin_a_sol_vault.key() @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin_a: Box<Account<'info, Coin>>,
+    #[account(mut)]
+    pub coin_a_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to send the sol.
+    #[account(mut)]
+    pub coin_a_sol_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        token::mint = coin_a.coin_mint,
+        token::authority = payer,
+    )]
+    pub coin_a_payer: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The user state of the payer on coin A, only read for the sell cooldown if it exists.
+    #[account(
+        seeds = [USER_STATE_SEED.as_bytes(), coin_a.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub coin_a_user_state: UncheckedAccount<'info>,
+    /// Optional position of the payer on coin A, updated with the sell.
+    #[account(mut,
+        constraint = coin_a_user_position.coin == coin_a.key() @ Error::UserPositionMismatch,
+        constraint = coin_a_user_position.owner == payer.key() @ Error::UserPositionMismatch,
+    )]
+    pub coin_a_user_position: Option<Box<Account<'info, UserPosition>>>,
+
+    /// The coin to buy.
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        constraint = coin_b.key() != coin_a.key() @ Error::IdenticalCoins,
+        constraint = coin_b.coin_vault == coin_b_vault.key() @ Error::CoinVaultMismatch,
+        constraint = coin_b.sol_vault == coin_b_sol_vault.key() @ Error::SolVaultAccountMismatch,
+    )]
+    pub coin_b: Box<Account<'info, Coin>>,
+    #[account(mut)]
+    pub coin_b_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This account is only used to receive the sol.
+    #[account(mut)]
+    pub coin_b_sol_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = coin_b_recipient.mint == coin_b.coin_mint.key() @ Error::CoinMintAccountMismatch
+    )]
+    pub coin_b_recipient: Box<Account<'info, TokenAccount>>,
+    /// Records the coin B bought by the owner of the coin B recipient.
+    #[account(init_if_needed,
+        payer = payer,
+        space = UserState::LEN,
+        seeds = [USER_STATE_SEED.as_bytes(), coin_b.key().as_ref(), coin_b_recipient.owner.as_ref()],
+        bump,
+    )]
+    pub coin_b_user_state: Box<Account<'info, UserState>>,
+    /// Optional position of the payer on coin B, updated with the buy.
+    #[account(mut,
+        constraint = coin_b_user_position.coin == coin_b.key() @ Error::UserPositionMismatch,
+        constraint = coin_b_user_position.owner == payer.key() @ Error::UserPositionMismatch,
+    )]
+    pub coin_b_user_position: Option<Box<Account<'info, UserPosition>>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize)]
+pub struct SwapCoinsArgs {
+    /// Amount of coin A to sell.
+    pub amount: u64,
+    /// Minimum amount of coin B to receive.
+    pub min_receive: u64,
+    /// Optional expiry of the swap, omitted by older clients.
+    pub expiry: Option<Expiry>,
+}
+
+impl AnchorDeserialize for SwapCoinsArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            amount: u64::deserialize_reader(reader)?,
+            min_receive: u64::deserialize_reader(reader)?,
+            expiry: Expiry::deserialize_trailing(reader)?,
+        })
+    }
+}
+
+pub fn handler(ctx: Context<SwapCoins>, args: SwapCoinsArgs) -> Result<()> {
+    Expiry::require_not_expired(args.expiry)?;
+
+    let accounts = ctx.accounts;
+    // No proof can be given here, coin B can only be bought with `buy` during its allowlist window.
+    accounts
+        .coin_b
+        .check_allowlist(&accounts.coin_b_recipient.owner, None)?;
+
+    // 1. Sell coin A, the sol is received by the payer.
+    let cooldown_tax_rate =
+        UserState::cooldown_sell_tax_rate(&accounts.config, &accounts.coin_a_user_state)?;
+    let sell = accounts.coin_a.sell(
+        &accounts.config,
+        &accounts.coin_a_vault,
+        &accounts.coin_a_sol_vault,
+        &accounts.fee_recipient,
+        &accounts.payer.to_account_info(),
+        &accounts.coin_a_payer,
+        &accounts.payer,
+        &accounts.token_program,
+        &accounts.system_program,
+        args.amount,
+        0,
+        cooldown_tax_rate,
+        accounts
+            .coin_a_user_position
+            .as_deref_mut()
+            .map(|p| &mut **p),
+    )?;
+
+    // 2. Buy coin B with all the sol received, including the fee.
+    let coin_b_copy = accounts.coin_b.clone();
+    let buy = accounts.coin_b.buy_exact_in(
+        &accounts.config,
+        &coin_b_copy.to_account_info(),
+        &accounts.coin_b_vault,
+        &accounts.coin_b_sol_vault,
+        &accounts.fee_recipient,
+        &accounts.coin_b_recipient.to_account_info(),
+        &accounts.payer,
+        &accounts.token_program,
+        &accounts.system_program,
+        sell.sol_amount,
+        true,
+        args.min_receive,
+        accounts
+            .coin_b_user_position
+            .as_deref_mut()
+            .map(|p| &mut **p),
+    )?;
+    accounts.coin_b_user_state.record_buy(
+        &accounts.config,
+        accounts.coin_b_recipient.owner,
+        &accounts.coin_b,
+        ctx.bumps.coin_b_user_state,
+        buy.coin_amount,
+    )
+}
@@ -0,0 +1,74 @@
+use crate::errors::Error;
+use crate::math::curve::CurveParams;
+use crate::state::Config;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, has_one = authority @ Error::AuthorityMismatch)]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+/// Settings to update. A `None` field keeps its current value.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateConfigArgs {
+    /// The maximum share of the supply the creator can buy in `create_and_buy`, in basis points.
+    /// 0 means no limit.
+    pub max_creator_buy_bps: Option<u16>,
+    /// Amount of sol escrowed with each order and paid to the keeper who fills it.
+    pub order_keeper_reward: Option<u64>,
+    /// The maximum share of the supply a wallet can buy on the bonding curve of a coin, in basis points.
+    /// 0 means no limit.
+    pub max_wallet_buy_bps: Option<u16>,
+    /// The extra fee rate of a buy in the creation slot of a coin, on top of the maker fee rate.
+    pub launch_tax_rate: Option<u32>,
+    /// The number of slots over which the launch tax decays linearly to 0.
+    pub launch_tax_slots: Option<u64>,
+    /// The number of slots after the last buy of a wallet during which its sells are rejected or taxed.
+    /// 0 means no cooldown.
+    pub sell_cooldown_slots: Option<u64>,
+    /// The extra fee rate of a sell during the cooldown, on top of the taker fee rate.
+    /// 0 means the sell is rejected instead.
+    pub cooldown_sell_tax_rate: Option<u32>,
+    /// The maximum change of the spot price a single trade on the bonding curve can cause, in basis points.
+    /// 0 means no limit.
+    pub max_price_impact_bps: Option<u16>,
+    /// The parameters of the bonding curve of the coins created from now on.
+    /// The coins already created keep their own.
+    pub curve_params: Option<CurveParams>,
+}
+
+pub fn handler(ctx: Context<UpdateConfig>, args: UpdateConfigArgs) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    if let Some(max_creator_buy_bps) = args.max_creator_buy_bps {
+        config.set_max_creator_buy_bps(max_creator_buy_bps)?;
+    }
+    if let Some(order_keeper_reward) = args.order_keeper_reward {
+        config.set_order_keeper_reward(order_keeper_reward);
+    }
+    if let Some(max_wallet_buy_bps) = args.max_wallet_buy_bps {
+        config.set_max_wallet_buy_bps(max_wallet_buy_bps)?;
+    }
+    if args.launch_tax_rate.is_some() || args.launch_tax_slots.is_some() {
+        let launch_tax_rate = args.launch_tax_rate.unwrap_or(config.launch_tax_rate);
+        let launch_tax_slots = args.launch_tax_slots.unwrap_or(config.launch_tax_slots);
+        config.set_launch_tax(launch_tax_rate, launch_tax_slots)?;
+    }
+    if args.sell_cooldown_slots.is_some() || args.cooldown_sell_tax_rate.is_some() {
+        let sell_cooldown_slots = args
+            .sell_cooldown_slots
+            .unwrap_or(config.sell_cooldown_slots);
+        let cooldown_sell_tax_rate = args
+            .cooldown_sell_tax_rate
+            .unwrap_or(config.cooldown_sell_tax_rate);
+        config.set_sell_cooldown(sell_cooldown_slots, cooldown_sell_tax_rate)?;
+    }
+    if let Some(max_price_impact_bps) = args.max_price_impact_bps {
+        config.set_max_price_impact_bps(max_price_impact_bps);
+    }
+    if let Some(curve_params) = args.curve_params {
+        config.set_curve_params(curve_params)?;
+    }
+    Ok(())
+}
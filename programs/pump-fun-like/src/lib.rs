@@ -26,6 +26,18 @@ pub mod pump_fun_like {
         update_fee::handler(ctx, args)
     }
 
+    pub fn update_config(ctx: Context<UpdateConfig>, args: UpdateConfigArgs) -> Result<()> {
+        update_config::handler(ctx, args)
+    }
+
+    pub fn realloc_config(ctx: Context<ReallocConfig>, args: ReallocConfigArgs) -> Result<()> {
+        realloc_config::handler(ctx, args)
+    }
+
+    pub fn realloc_coin(ctx: Context<ReallocCoin>) -> Result<()> {
+        realloc_coin::handler(ctx)
+    }
+
     pub fn create<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, Create<'info>>,
         args: CreateArgs,
@@ -33,6 +45,13 @@ pub mod pump_fun_like {
         create::handler(ctx, args)
     }
 
+    pub fn create_and_buy<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CreateAndBuy<'info>>,
+        args: CreateAndBuyArgs,
+    ) -> Result<()> {
+        create_and_buy::handler(ctx, args)
+    }
+
     pub fn buy(ctx: Context<Buy>, args: BuyArgs) -> Result<()> {
         buy::handler(ctx, args)
     }
@@ -48,4 +67,82 @@ pub mod pump_fun_like {
     pub fn sell_exact_out(ctx: Context<SellExactOut>, args: SellExactOutArgs) -> Result<()> {
         sell_exact_out::handler(ctx, args)
     }
+
+    pub fn quote_buy(ctx: Context<QuoteBuy>, args: QuoteBuyArgs) -> Result<Quote> {
+        quote_buy::handler(ctx, args)
+    }
+
+    pub fn quote_buy_exact_in(
+        ctx: Context<QuoteBuyExactIn>,
+        args: QuoteBuyExactInArgs,
+    ) -> Result<Quote> {
+        quote_buy_exact_in::handler(ctx, args)
+    }
+
+    pub fn quote_sell(ctx: Context<QuoteSell>, args: QuoteSellArgs) -> Result<Quote> {
+        quote_sell::handler(ctx, args)
+    }
+
+    pub fn quote_sell_exact_out(
+        ctx: Context<QuoteSellExactOut>,
+        args: QuoteSellExactOutArgs,
+    ) -> Result<Quote> {
+        quote_sell_exact_out::handler(ctx, args)
+    }
+
+    pub fn sell_fraction(ctx: Context<SellFraction>, args: SellFractionArgs) -> Result<()> {
+        sell_fraction::handler(ctx, args)
+    }
+
+    pub fn swap_coins(ctx: Context<SwapCoins>, args: SwapCoinsArgs) -> Result<()> {
+        swap_coins::handler(ctx, args)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        open_position::handler(ctx)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        close_position::handler(ctx)
+    }
+
+    pub fn place_order(ctx: Context<PlaceOrder>, args: PlaceOrderArgs) -> Result<()> {
+        place_order::handler(ctx, args)
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        cancel_order::handler(ctx)
+    }
+
+    pub fn execute_order(ctx: Context<ExecuteOrder>) -> Result<()> {
+        execute_order::handler(ctx)
+    }
+
+    pub fn create_dca(ctx: Context<CreateDca>, args: CreateDcaArgs) -> Result<()> {
+        create_dca::handler(ctx, args)
+    }
+
+    pub fn cancel_dca(ctx: Context<CancelDca>) -> Result<()> {
+        cancel_dca::handler(ctx)
+    }
+
+    pub fn execute_dca(ctx: Context<ExecuteDca>) -> Result<()> {
+        execute_dca::handler(ctx)
+    }
+
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        migrate::handler(ctx)
+    }
+
+    pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
+        create_pool::handler(ctx)
+    }
+
+    pub fn pool_swap(ctx: Context<PoolSwap>, args: PoolSwapArgs) -> Result<()> {
+        pool_swap::handler(ctx, args)
+    }
+
+    pub fn set_halted(ctx: Context<SetHalted>, args: SetHaltedArgs) -> Result<()> {
+        set_halted::handler(ctx, args)
+    }
 }
@@ -1,5 +1,5 @@
-use super::math;
-use crate::constants::{MAX_COIN_SUPPLY, MAX_VIRTUAL_COIN_RESERVES, VIRTUAL_SOL_REVERSES};
+use super::{curve::CurveParams, math};
+use crate::errors::Error;
 
 #[derive(PartialEq, Eq)]
 pub enum Side {
@@ -9,110 +9,302 @@ pub enum Side {
 
 /// Calculates the amount of sol to be paid.
 ///
-/// The `remaining_coin_supply` is the remaining supply of the coin, including the [crate::constants::REVERSE_COINS].
+/// The `remaining_coin_supply` is the remaining supply of the coin, including the `reverse_coins` of the `params`.
 /// The `amount` is the amount of coin to be bought.
-pub fn buy(remaining_coin_supply: u64, amount: u64) -> u64 {
-    calc(remaining_coin_supply, amount, Side::Buy)
+pub fn buy(params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64, Error> {
+    calc(params, remaining_coin_supply, amount, Side::Buy)
 }
 
 /// Calculates the amount of sol to be received.
 ///
-/// The `remaining_coin_supply` is the remaining supply of the coin, including the [crate::constants::REVERSE_COINS].
+/// The `remaining_coin_supply` is the remaining supply of the coin, including the `reverse_coins` of the `params`.
 /// The `amount` is the amount of coin to be sold.
-pub fn sell(remaining_coin_supply: u64, amount: u64) -> u64 {
-    calc(remaining_coin_supply, amount, Side::Sell)
+pub fn sell(params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64, Error> {
+    calc(params, remaining_coin_supply, amount, Side::Sell)
 }
 
-/// Calculates the amount of sol to be paid or received.
+/// The scale of the fixed point spot price.
+pub const SPOT_PRICE_SCALE: u128 = 1e12 as u128;
+
+/// Calculates the spot price of the coin, in lamports per smallest unit of coin scaled by [SPOT_PRICE_SCALE].
 ///
-/// The `remaining_coin_supply` is the remaining supply of the coin, including the [crate::constants::REVERSE_COINS].
-fn calc(remaining_coin_supply: u64, amount: u64, side: Side) -> u64 {
-    let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
+/// The `remaining_coin_supply` is the remaining supply of the coin, including the `reverse_coins` of the `params`.
+/// The price is the ratio of the virtual sol reserves `virtual_sol_reverses * max_virtual_coin_reserves / (max_virtual_coin_reserves - already_sold)`
+/// to the virtual coin reserves `max_virtual_coin_reserves - already_sold`.
+pub fn spot_price(params: &CurveParams, remaining_coin_supply: u64) -> Result<u128, Error> {
+    let already_sold = params
+        .max_coin_supply
+        .checked_sub(remaining_coin_supply)
+        .ok_or(Error::MathUnderflow)?;
+    let virtual_coin_reserves = params
+        .max_virtual_coin_reserves()
+        .checked_sub(already_sold)
+        .filter(|virtual_coin_reserves| *virtual_coin_reserves > 0)
+        .ok_or(Error::MathUnderflow)? as u128;
+    let virtual_sol_reserves = (params.virtual_sol_reverses as u128)
+        .checked_mul(params.max_virtual_coin_reserves() as u128)
+        .ok_or(Error::MathOverflow)?
+        / virtual_coin_reserves;
+    Ok(virtual_sol_reserves
+        .checked_mul(SPOT_PRICE_SCALE)
+        .ok_or(Error::MathOverflow)?
+        / virtual_coin_reserves)
+}
 
-    let numerator: u128 = already_sold as u128 * VIRTUAL_SOL_REVERSES as u128;
-    let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold;
+/// Calculates the amount of sol to be paid or received.
+///
+/// The `remaining_coin_supply` is the remaining supply of the coin, including the `reverse_coins` of the `params`.
+fn calc(
+    params: &CurveParams,
+    remaining_coin_supply: u64,
+    amount: u64,
+    side: Side,
+) -> Result<u64, Error> {
+    let already_sold = params
+        .max_coin_supply
+        .checked_sub(remaining_coin_supply)
+        .ok_or(Error::MathUnderflow)?;
+    let already_sold_target = match side {
+        Side::Buy => already_sold
+            .checked_add(amount)
+            .ok_or(Error::MathOverflow)?,
+        Side::Sell => already_sold
+            .checked_sub(amount)
+            .ok_or(Error::MathUnderflow)?,
+    };
+    let (numerator, denominator) = sol_reserves(params, already_sold)?;
+    let (numerator_target, denominator_target) = sol_reserves(params, already_sold_target)?;
 
     match side {
         Side::Buy => {
-            let sol_before = numerator / denominator as u128;
-
-            let already_sold_target = already_sold + amount;
-            let numerator = already_sold_target as u128 * VIRTUAL_SOL_REVERSES as u128;
-            let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold_target;
-            let sol_after = math::ceil_div(numerator, denominator as u128);
-            (sol_after - sol_before) as u64
+            let sol_before = numerator / denominator;
+            let sol_after = math::ceil_div(numerator_target, denominator_target);
+            let sol = sol_after
+                .checked_sub(sol_before)
+                .ok_or(Error::MathUnderflow)?;
+            u64::try_from(sol).map_err(|_| Error::MathOverflow)
         }
         Side::Sell => {
-            let sol_before = math::ceil_div(numerator, denominator as u128);
-
-            let already_sold_target = already_sold - amount;
-            let numerator = already_sold_target as u128 * VIRTUAL_SOL_REVERSES as u128;
-            let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold_target;
-            let sol_after = numerator / denominator as u128;
-            if sol_before <= sol_after {
-                0
-            } else {
-                (sol_before - sol_after) as u64
-            }
+            let sol_before = math::ceil_div(numerator, denominator);
+            let sol_after = numerator_target / denominator_target;
+            u64::try_from(sol_before.saturating_sub(sol_after)).map_err(|_| Error::MathOverflow)
         }
     }
 }
 
+/// Returns the sol reserves after `already_sold` coins are sold, as a fraction `(numerator, denominator)`.
+fn sol_reserves(params: &CurveParams, already_sold: u64) -> Result<(u128, u128), Error> {
+    let numerator = (already_sold as u128)
+        .checked_mul(params.virtual_sol_reverses as u128)
+        .ok_or(Error::MathOverflow)?;
+    // the virtual coin reserves never run out, even past the `max_coin_supply`
+    let denominator = params
+        .max_virtual_coin_reserves()
+        .checked_sub(already_sold)
+        .filter(|denominator| *denominator > 0)
+        .ok_or(Error::MathUnderflow)?;
+    Ok((numerator, denominator as u128))
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use crate::{
-        constants::{FEE_RATE_BASIS_POINT, REVERSE_COINS},
-        math::fee_math,
+        constants::FEE_RATE_BASIS_POINT,
+        math::{curve::test_params::*, fee_math},
     };
 
     use super::*;
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_buy_already_sold_is_0_and_buy_all() {
-        let sol = buy(MAX_COIN_SUPPLY, MAX_COIN_SUPPLY - REVERSE_COINS);
+        let sol = buy(&PARAMS, MAX_COIN_SUPPLY, MAX_COIN_SUPPLY - REVERSE_COINS).unwrap();
         assert_eq!(sol, 85005359057);
-        let sol_with_fee = sol + fee_math::buy(sol, FEE_RATE_BASIS_POINT * 1 / 100);
+        let sol_with_fee = sol + fee_math::buy(sol, FEE_RATE_BASIS_POINT * 1 / 100).unwrap();
         assert_eq!(sol_with_fee, 85855412647);
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_buy_already_sold_is_0_and_buy_half() {
-        let sol = buy(MAX_COIN_SUPPLY, (MAX_COIN_SUPPLY - REVERSE_COINS) / 2);
+        let sol = buy(
+            &PARAMS,
+            MAX_COIN_SUPPLY,
+            (MAX_COIN_SUPPLY - REVERSE_COINS) / 2,
+        )
+        .unwrap();
         assert_eq!(sol, 17586665681 + 1);
-        let sol_with_fee = sol + fee_math::buy(sol, FEE_RATE_BASIS_POINT * 1 / 100);
+        let sol_with_fee = sol + fee_math::buy(sol, FEE_RATE_BASIS_POINT * 1 / 100).unwrap();
         assert_eq!(sol_with_fee, 17762532338);
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_buy_already_sold_is_0_and_buy_1() {
-        let sol = buy(MAX_COIN_SUPPLY, 1);
+        let sol = buy(&PARAMS, MAX_COIN_SUPPLY, 1).unwrap();
         assert_eq!(sol, 0 + 1);
     }
 
     #[test]
     fn test_buy_already_sold_is_2329803488261_and_buy_17514483287344() {
         let already_sold = 2329803488261;
-        let sol = buy(MAX_COIN_SUPPLY - already_sold, 17514483287344);
+        let sol = buy(&PARAMS, MAX_COIN_SUPPLY - already_sold, 17514483287344).unwrap();
         assert_eq!(sol, 500000001);
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_pump_fun() {
         let real_token_reserves: u64 = 589359216751050;
         let sol = buy(
+            &PARAMS,
             real_token_reserves + REVERSE_COINS,
             1100e4 as u64 * 1e6 as u64,
-        );
+        )
+        .unwrap();
         assert_eq!(sol, 474619833);
-        let sol_with_fee = sol + fee_math::buy(sol, FEE_RATE_BASIS_POINT * 1 / 100);
+        let sol_with_fee = sol + fee_math::buy(sol, FEE_RATE_BASIS_POINT * 1 / 100).unwrap();
         assert_eq!(sol_with_fee, 479366031);
 
         let sol = sell(
+            &PARAMS,
             real_token_reserves + REVERSE_COINS,
             1100e4 as u64 * 1e6 as u64,
-        );
+        )
+        .unwrap();
         assert_eq!(sol, 462757832);
-        let sol_with_fee = sol - fee_math::sell(sol, FEE_RATE_BASIS_POINT * 1 / 100);
+        let sol_with_fee = sol - fee_math::sell(sol, FEE_RATE_BASIS_POINT * 1 / 100).unwrap();
         assert_eq!(sol_with_fee, 458130254);
     }
+
+    #[test]
+    fn test_spot_price_already_sold_is_0() {
+        // 30 sol over the 1.073 billion virtual coins
+        assert_eq!(spot_price(&PARAMS, MAX_COIN_SUPPLY).unwrap(), 27958993);
+    }
+
+    #[test]
+    fn test_spot_price_all_sold() {
+        assert_eq!(spot_price(&PARAMS, REVERSE_COINS).unwrap(), 410880168);
+    }
+
+    #[test]
+    fn test_spot_price_remaining_above_max_supply() {
+        assert!(matches!(
+            spot_price(&PARAMS, MAX_COIN_SUPPLY + 1),
+            Err(Error::MathUnderflow)
+        ));
+    }
+
+    #[test]
+    fn test_spot_price_is_the_marginal_price() {
+        for already_sold in [0, 2329803488261, (MAX_COIN_SUPPLY - REVERSE_COINS) / 2] {
+            let remaining_coin_supply = MAX_COIN_SUPPLY - already_sold;
+            let amount = 1e9 as u64;
+            let marginal = buy(&PARAMS, remaining_coin_supply, amount).unwrap() as u128
+                * SPOT_PRICE_SCALE
+                / amount as u128;
+            let price = spot_price(&PARAMS, remaining_coin_supply).unwrap();
+            assert!(marginal >= price);
+            assert!(marginal - price < price / 1000);
+        }
+    }
+
+    #[test]
+    fn test_sell_more_than_sold_underflows() {
+        let already_sold = 2329803488261;
+        assert!(matches!(
+            sell(&PARAMS, MAX_COIN_SUPPLY - already_sold, already_sold + 1),
+            Err(Error::MathUnderflow)
+        ));
+        assert!(matches!(
+            buy(&PARAMS, MAX_COIN_SUPPLY + 1, 1),
+            Err(Error::MathUnderflow)
+        ));
+    }
+
+    #[test]
+    fn test_buy_past_the_virtual_coin_reserves_fails() {
+        assert!(matches!(
+            buy(&PARAMS, MAX_COIN_SUPPLY, PARAMS.max_virtual_coin_reserves()),
+            Err(Error::MathUnderflow)
+        ));
+        assert!(matches!(
+            buy(&PARAMS, MAX_COIN_SUPPLY - 1, u64::MAX),
+            Err(Error::MathOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_spot_price_increases_with_buys() {
+        let mut remaining_coin_supply = MAX_COIN_SUPPLY;
+        let mut price = spot_price(&PARAMS, remaining_coin_supply).unwrap();
+        while remaining_coin_supply > REVERSE_COINS {
+            remaining_coin_supply -= (MAX_COIN_SUPPLY - REVERSE_COINS) / 10;
+            let next = spot_price(&PARAMS, remaining_coin_supply).unwrap();
+            assert!(next > price);
+            price = next;
+        }
+    }
+
+    /// A remaining coin supply anywhere on the bonding curve, and an amount of coin available to buy from it.
+    fn buyable() -> impl Strategy<Value = (u64, u64)> {
+        (REVERSE_COINS..=MAX_COIN_SUPPLY)
+            .prop_flat_map(|remaining| (Just(remaining), 0..=remaining - REVERSE_COINS))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_buy_then_sell_never_profits((remaining_coin_supply, amount) in buyable()) {
+            let paid = buy(&PARAMS, remaining_coin_supply, amount).unwrap();
+            let received = sell(&PARAMS, remaining_coin_supply - amount, amount).unwrap();
+            prop_assert!(received <= paid);
+        }
+
+        #[test]
+        fn prop_buy_and_sell_never_exceed_the_reserves((remaining_coin_supply, amount) in buyable()) {
+            let buy_all = buy(&PARAMS, MAX_COIN_SUPPLY, SELLABLE_COINS).unwrap();
+            let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
+            prop_assert!(buy(&PARAMS, remaining_coin_supply, amount).unwrap() <= buy_all);
+            prop_assert!(
+                sell(&PARAMS, remaining_coin_supply, amount.min(already_sold)).unwrap()
+                    <= buy(&PARAMS, MAX_COIN_SUPPLY, already_sold).unwrap()
+            );
+        }
+
+        #[test]
+        fn prop_spot_price_rises_with_coins_sold((remaining_coin_supply, amount) in buyable()) {
+            let price_before = spot_price(&PARAMS, remaining_coin_supply).unwrap();
+            let price_after = spot_price(&PARAMS, remaining_coin_supply - amount).unwrap();
+            prop_assert!(price_after >= price_before);
+            if amount > 0 {
+                prop_assert!(price_after > price_before);
+            }
+        }
+
+        #[test]
+        fn prop_buy_cost_rises_with_amount((remaining_coin_supply, amount) in buyable()) {
+            prop_assume!(amount < remaining_coin_supply - REVERSE_COINS);
+            // a single unit of coin may cost less than 1 lamport, so the cost only never falls
+            prop_assert!(
+                buy(&PARAMS, remaining_coin_supply, amount + 1).unwrap()
+                    >= buy(&PARAMS, remaining_coin_supply, amount).unwrap()
+            );
+        }
+
+        #[test]
+        fn prop_buy_costs_more_after_coins_sold(
+            (remaining_coin_supply, sold, amount) in buyable().prop_flat_map(|(remaining, sold)| {
+                (Just(remaining), Just(sold), 0..=remaining - REVERSE_COINS - sold)
+            })
+        ) {
+            // each of the two buys rounds in favor of the curve by less than 1 lamport
+            prop_assert!(
+                buy(&PARAMS, remaining_coin_supply - sold, amount).unwrap() + 1
+                    >= buy(&PARAMS, remaining_coin_supply, amount).unwrap()
+            );
+        }
+    }
 }
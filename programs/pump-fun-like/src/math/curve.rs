@@ -0,0 +1,861 @@
+use anchor_lang::prelude::*;
+
+use super::{coin_math, exponential_math, linear_math, math, sol_math};
+use crate::constants::{BASIS_POINTS, MAX_DECIMALS};
+use crate::errors::Error;
+
+/// The family of the bonding curve of a coin.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum CurveKind {
+    /// The virtual constant product curve, `(virtual_sol + sol) * (virtual_coin - sold) = k`.
+    #[default]
+    ConstantProduct,
+    /// The price grows linearly with the coins already sold.
+    Linear,
+    /// The price doubles every [CurveParams::exponential_doubling_coins] coins sold.
+    Exponential,
+}
+
+impl CurveKind {
+    pub const ALL: [CurveKind; 3] = [
+        CurveKind::ConstantProduct,
+        CurveKind::Linear,
+        CurveKind::Exponential,
+    ];
+}
+
+/// The supply and the params of each bonding curve, set on the config and copied onto each coin at create.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CurveParams {
+    /// The supply of a coin minted at create, including the `reverse_coins`.
+    pub max_coin_supply: u64,
+    /// The coins kept off the bonding curve, migrated with the sol once all the others are sold.
+    pub reverse_coins: u64,
+    /// The virtual coin reserves of the constant product curve, on top of the `max_coin_supply`.
+    pub virtual_coin_reserves: u64,
+    /// The virtual sol reserves of the constant product curve.
+    pub virtual_sol_reverses: u64,
+    /// The decimals of the coin the supply and the coin params of the curves are written in.
+    pub decimals: u8,
+    /// The coin offset of the linear curve, which holds `already_sold * (already_sold + linear_offset) / linear_scale`
+    /// sol, so the price starts at `linear_offset / linear_scale` and grows by `2 / linear_scale` per coin.
+    pub linear_offset: u64,
+    /// The scale of the linear curve, which is not offered if both its params are 0.
+    pub linear_scale: u128,
+    /// The coins over which the price of the exponential curve doubles, the curve holds
+    /// `exponential_sol_scale * (2 ^ (already_sold / exponential_doubling_coins) - 1)` sol.
+    pub exponential_doubling_coins: u64,
+    /// The sol scale of the exponential curve, which is not offered if both its params are 0.
+    pub exponential_sol_scale: u64,
+}
+
+impl CurveParams {
+    pub const LEN: usize = 8 * 4 + 1 + 8 + 16 + 8 + 8;
+
+    /// The params every coin was priced with before they were stored on it, in 6 decimals.
+    pub const LEGACY: CurveParams = CurveParams {
+        max_coin_supply: (10e8 * 1e6) as u64,
+        reverse_coins: 2_0690_0000 * 1e6 as u64,
+        virtual_coin_reserves: 7300_0000e6 as u64,
+        virtual_sol_reverses: 30e9 as u64,
+        decimals: 6,
+        linear_offset: 2_8000_0000e6 as u64,
+        linear_scale: 1e19 as u128,
+        exponential_doubling_coins: 2_0000_0000e6 as u64,
+        exponential_sol_scale: 6e9 as u64,
+    };
+
+    /// Checks that the bonding curve has coins to sell, and the params of each curve offered by them.
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.reverse_coins > 0
+                && self.reverse_coins < self.max_coin_supply
+                && self.decimals <= MAX_DECIMALS,
+            Error::InvalidCurveParams
+        );
+        for kind in CurveKind::ALL {
+            if kind.is_offered(self) {
+                kind.validate(self)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the `kind` curve is offered by the params, with a positive price and sol reserves that fit in lamports.
+    pub fn validate_kind(&self, kind: CurveKind) -> Result<()> {
+        require!(kind.is_offered(self), Error::InvalidCurveParams);
+        kind.validate(self)
+    }
+
+    /// Returns the params of a coin with `decimals` decimals, with the supply and the coin params of the curves
+    /// rescaled so that the sol price of a whole coin stays the same.
+    pub fn with_decimals(&self, decimals: u8) -> Result<CurveParams> {
+        require!(decimals <= MAX_DECIMALS, Error::InvalidDecimals);
+        let rescale = |coins: u128| -> Result<u128> {
+            if decimals >= self.decimals {
+                let factor = 10u128.pow((decimals - self.decimals) as u32);
+                Ok(coins.checked_mul(factor).ok_or(Error::InvalidDecimals)?)
+            } else {
+                let factor = 10u128.pow((self.decimals - decimals) as u32);
+                require!(coins / factor * factor == coins, Error::InvalidDecimals);
+                Ok(coins / factor)
+            }
+        };
+        let rescale_u64 = |coins: u64| -> Result<u64> {
+            Ok(u64::try_from(rescale(coins as u128)?).map_err(|_| Error::InvalidDecimals)?)
+        };
+        let params = CurveParams {
+            max_coin_supply: rescale_u64(self.max_coin_supply)?,
+            reverse_coins: rescale_u64(self.reverse_coins)?,
+            virtual_coin_reserves: rescale_u64(self.virtual_coin_reserves)?,
+            virtual_sol_reverses: self.virtual_sol_reverses,
+            decimals,
+            linear_offset: rescale_u64(self.linear_offset)?,
+            // the scale divides the square of the coins, so it is rescaled twice
+            linear_scale: rescale(rescale(self.linear_scale)?)?,
+            exponential_doubling_coins: rescale_u64(self.exponential_doubling_coins)?,
+            exponential_sol_scale: self.exponential_sol_scale,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Returns the coins sold on the bonding curve before it completes.
+    pub fn sellable_coins(&self) -> u64 {
+        self.max_coin_supply - self.reverse_coins
+    }
+
+    pub fn max_virtual_coin_reserves(&self) -> u64 {
+        self.max_coin_supply + self.virtual_coin_reserves
+    }
+
+    /// Returns the progress of the bonding curve toward completion in basis points, the share of the sellable coins
+    /// already sold, rounded down so it only reaches [BASIS_POINTS] once the remaining supply is the `reverse_coins`.
+    pub fn progress_bps(&self, remaining_coin_supply: u64) -> Result<u16> {
+        let already_sold = already_sold(self, remaining_coin_supply)?;
+        let progress = already_sold.min(self.sellable_coins()) as u128 * BASIS_POINTS as u128
+            / self.sellable_coins() as u128;
+        Ok(progress as u16)
+    }
+}
+
+/// A bonding curve, given by the sol reserves as a function of the coins already sold.
+///
+/// All `remaining_coin_supply` parameters include the `reverse_coins` of the [CurveParams].
+/// The trade functions round in favor of the curve, see why_round_down.png.
+pub trait BondingCurve {
+    /// Returns whether the params of the curve are set, so coins can be created on it.
+    fn is_offered(&self, params: &CurveParams) -> bool;
+
+    /// Checks the params of the curve, which must keep its intermediate values in `u128` up to the `max_coin_supply`
+    /// and its sol reserves in lamports up to the sellable coins.
+    fn validate(&self, params: &CurveParams) -> Result<()>;
+
+    /// Returns the sol reserves after `already_sold` coins are sold, as a fraction `(numerator, denominator)`.
+    fn sol_reserves(&self, params: &CurveParams, already_sold: u64) -> (u128, u128);
+
+    /// Returns the largest amount of coins already sold whose sol reserves do not exceed `sol_reserves`.
+    fn already_sold(&self, params: &CurveParams, sol_reserves: u128) -> u128;
+
+    /// Returns the spot price in lamports per smallest unit of coin, scaled by [coin_math::SPOT_PRICE_SCALE].
+    fn spot_price(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u128>;
+
+    /// Returns the fully diluted market cap in lamports, all the `max_coin_supply` valued at the spot price, rounded down.
+    fn market_cap(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u64> {
+        let market_cap = self
+            .spot_price(params, remaining_coin_supply)?
+            .checked_mul(params.max_coin_supply as u128)
+            .ok_or(Error::MathOverflow)?
+            / coin_math::SPOT_PRICE_SCALE;
+        Ok(u64::try_from(market_cap).map_err(|_| Error::MathOverflow)?)
+    }
+
+    /// Calculates the amount of sol to be paid to buy `amount` coins.
+    fn buy(&self, params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64> {
+        let already_sold = already_sold(params, remaining_coin_supply)?;
+        let already_sold_target = already_sold
+            .checked_add(amount)
+            .ok_or(Error::MathOverflow)?;
+        let sol_before = self.floor_sol_reserves(params, already_sold);
+        let sol_after = self.ceil_sol_reserves(params, already_sold_target);
+        let sol = sol_after
+            .checked_sub(sol_before)
+            .ok_or(Error::MathUnderflow)?;
+        Ok(u64::try_from(sol).map_err(|_| Error::MathOverflow)?)
+    }
+
+    /// Calculates the amount of sol to be received by selling `amount` coins.
+    fn sell(&self, params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64> {
+        let already_sold = already_sold(params, remaining_coin_supply)?;
+        let already_sold_target = already_sold
+            .checked_sub(amount)
+            .ok_or(Error::MathUnderflow)?;
+        let sol_before = self.ceil_sol_reserves(params, already_sold);
+        let sol_after = self.floor_sol_reserves(params, already_sold_target);
+        Ok(u64::try_from(sol_before.saturating_sub(sol_after)).map_err(|_| Error::MathOverflow)?)
+    }
+
+    /// Calculates the amount of coin to be bought by paying `amount` sol, clamped to the sellable coins.
+    fn buy_exact_in(
+        &self,
+        params: &CurveParams,
+        remaining_coin_supply: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        let already_sold = already_sold(params, remaining_coin_supply)?;
+        let sol_after = self
+            .floor_sol_reserves(params, already_sold)
+            .checked_add(amount as u128)
+            .ok_or(Error::MathOverflow)?;
+        let coin_after = self.already_sold(params, sol_after);
+        if coin_after <= already_sold as u128 {
+            Ok(0)
+        } else if coin_after >= params.sellable_coins() as u128 {
+            Ok(params
+                .sellable_coins()
+                .checked_sub(already_sold)
+                .ok_or(Error::MathUnderflow)?)
+        } else {
+            Ok((coin_after - already_sold as u128) as u64)
+        }
+    }
+
+    /// Calculates the amount of coin to be sold to receive `amount` sol.
+    fn sell_exact_out(
+        &self,
+        params: &CurveParams,
+        remaining_coin_supply: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        let already_sold = already_sold(params, remaining_coin_supply)?;
+        let sol_before = self.floor_sol_reserves(params, already_sold);
+        if sol_before < amount as u128 {
+            return err!(Error::ExactOutTooLarge);
+        } else if sol_before == amount as u128 {
+            return Ok(already_sold);
+        }
+
+        let coin_after = self.already_sold(params, sol_before - amount as u128);
+        if coin_after >= already_sold as u128 {
+            err!(Error::UnexpectExactOutput)
+        } else {
+            Ok(already_sold - coin_after as u64)
+        }
+    }
+
+    fn floor_sol_reserves(&self, params: &CurveParams, already_sold: u64) -> u128 {
+        let (numerator, denominator) = self.sol_reserves(params, already_sold);
+        numerator / denominator
+    }
+
+    fn ceil_sol_reserves(&self, params: &CurveParams, already_sold: u64) -> u128 {
+        let (numerator, denominator) = self.sol_reserves(params, already_sold);
+        math::ceil_div(numerator, denominator)
+    }
+}
+
+/// Returns the coins already sold on the bonding curve.
+pub(super) fn already_sold(params: &CurveParams, remaining_coin_supply: u64) -> Result<u64> {
+    Ok(params
+        .max_coin_supply
+        .checked_sub(remaining_coin_supply)
+        .ok_or(Error::MathUnderflow)?)
+}
+
+/// The virtual constant product curve of [coin_math] and [sol_math].
+pub struct ConstantProductCurve;
+
+impl BondingCurve for ConstantProductCurve {
+    fn is_offered(&self, params: &CurveParams) -> bool {
+        params.virtual_coin_reserves > 0 || params.virtual_sol_reverses > 0
+    }
+
+    fn validate(&self, params: &CurveParams) -> Result<()> {
+        require!(
+            params.virtual_coin_reserves > 0 && params.virtual_sol_reverses > 0,
+            Error::InvalidCurveParams
+        );
+        let max_virtual_coin_reserves = params
+            .max_coin_supply
+            .checked_add(params.virtual_coin_reserves)
+            .ok_or(Error::InvalidCurveParams)?;
+        let min_virtual_coin_reserves = params
+            .virtual_coin_reserves
+            .checked_add(params.reverse_coins)
+            .ok_or(Error::InvalidCurveParams)?;
+        // the virtual sol reserves peak once all the sellable coins are sold
+        let max_virtual_sol_reserves = params.virtual_sol_reverses as u128
+            * max_virtual_coin_reserves as u128
+            / min_virtual_coin_reserves as u128;
+        require!(
+            max_virtual_sol_reserves <= u64::MAX as u128,
+            Error::InvalidCurveParams
+        );
+        Ok(())
+    }
+
+    fn sol_reserves(&self, params: &CurveParams, already_sold: u64) -> (u128, u128) {
+        (
+            already_sold as u128 * params.virtual_sol_reverses as u128,
+            (params.max_virtual_coin_reserves() - already_sold) as u128,
+        )
+    }
+
+    fn already_sold(&self, params: &CurveParams, sol_reserves: u128) -> u128 {
+        sol_reserves * params.max_virtual_coin_reserves() as u128
+            / (params.virtual_sol_reverses as u128 + sol_reserves)
+    }
+
+    fn spot_price(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u128> {
+        Ok(coin_math::spot_price(params, remaining_coin_supply)?)
+    }
+
+    fn buy(&self, params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64> {
+        Ok(coin_math::buy(params, remaining_coin_supply, amount)?)
+    }
+
+    fn sell(&self, params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64> {
+        Ok(coin_math::sell(params, remaining_coin_supply, amount)?)
+    }
+
+    fn buy_exact_in(
+        &self,
+        params: &CurveParams,
+        remaining_coin_supply: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        Ok(sol_math::buy(params, remaining_coin_supply, amount)?)
+    }
+
+    fn sell_exact_out(
+        &self,
+        params: &CurveParams,
+        remaining_coin_supply: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        Ok(sol_math::sell(params, remaining_coin_supply, amount)?)
+    }
+}
+
+impl CurveKind {
+    fn curve(&self) -> &'static dyn BondingCurve {
+        match self {
+            CurveKind::ConstantProduct => &ConstantProductCurve,
+            CurveKind::Linear => &linear_math::LinearCurve,
+            CurveKind::Exponential => &exponential_math::ExponentialCurve,
+        }
+    }
+}
+
+impl BondingCurve for CurveKind {
+    fn is_offered(&self, params: &CurveParams) -> bool {
+        self.curve().is_offered(params)
+    }
+
+    fn validate(&self, params: &CurveParams) -> Result<()> {
+        self.curve().validate(params)
+    }
+
+    fn sol_reserves(&self, params: &CurveParams, already_sold: u64) -> (u128, u128) {
+        self.curve().sol_reserves(params, already_sold)
+    }
+
+    fn already_sold(&self, params: &CurveParams, sol_reserves: u128) -> u128 {
+        self.curve().already_sold(params, sol_reserves)
+    }
+
+    fn spot_price(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u128> {
+        self.curve().spot_price(params, remaining_coin_supply)
+    }
+
+    fn buy(&self, params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64> {
+        self.curve().buy(params, remaining_coin_supply, amount)
+    }
+
+    fn sell(&self, params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64> {
+        self.curve().sell(params, remaining_coin_supply, amount)
+    }
+
+    fn buy_exact_in(
+        &self,
+        params: &CurveParams,
+        remaining_coin_supply: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        self.curve()
+            .buy_exact_in(params, remaining_coin_supply, amount)
+    }
+
+    fn sell_exact_out(
+        &self,
+        params: &CurveParams,
+        remaining_coin_supply: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        self.curve()
+            .sell_exact_out(params, remaining_coin_supply, amount)
+    }
+}
+
+/// The pump.fun curve parameters the math tests are written against.
+#[cfg(test)]
+pub mod test_params {
+    use super::CurveParams;
+
+    pub const PARAMS: CurveParams = CurveParams::LEGACY;
+    pub const MAX_COIN_SUPPLY: u64 = (10e8 * 1e6) as u64;
+    pub const REVERSE_COINS: u64 = 2_0690_0000 * 1e6 as u64;
+    pub const SELLABLE_COINS: u64 = MAX_COIN_SUPPLY - REVERSE_COINS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_params::*;
+    use super::*;
+    use crate::constants::DECIMALS;
+
+    /// The constant product curve without the overrides of [coin_math] and [sol_math].
+    struct GenericConstantProductCurve;
+
+    impl BondingCurve for GenericConstantProductCurve {
+        fn is_offered(&self, params: &CurveParams) -> bool {
+            ConstantProductCurve.is_offered(params)
+        }
+
+        fn validate(&self, params: &CurveParams) -> Result<()> {
+            ConstantProductCurve.validate(params)
+        }
+
+        fn sol_reserves(&self, params: &CurveParams, already_sold: u64) -> (u128, u128) {
+            ConstantProductCurve.sol_reserves(params, already_sold)
+        }
+
+        fn already_sold(&self, params: &CurveParams, sol_reserves: u128) -> u128 {
+            ConstantProductCurve.already_sold(params, sol_reserves)
+        }
+
+        fn spot_price(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u128> {
+            ConstantProductCurve.spot_price(params, remaining_coin_supply)
+        }
+    }
+
+    #[test]
+    fn test_generic_curve_matches_constant_product_math() {
+        let (generic, curve) = (GenericConstantProductCurve, ConstantProductCurve);
+        for already_sold in [0, 2692001940000, 589359216751050, SELLABLE_COINS - 1] {
+            let remaining_coin_supply = MAX_COIN_SUPPLY - already_sold;
+            for amount in [1, 1e6 as u64, 1e12 as u64, SELLABLE_COINS - already_sold] {
+                assert_eq!(
+                    generic.buy(&PARAMS, remaining_coin_supply, amount).unwrap(),
+                    curve.buy(&PARAMS, remaining_coin_supply, amount).unwrap()
+                );
+            }
+            for amount in [1.min(already_sold), already_sold / 3, already_sold] {
+                assert_eq!(
+                    generic
+                        .sell(&PARAMS, remaining_coin_supply, amount)
+                        .unwrap(),
+                    curve.sell(&PARAMS, remaining_coin_supply, amount).unwrap()
+                );
+            }
+            for amount in [1, 1e8 as u64, 1e9 as u64, 100e9 as u64] {
+                assert_eq!(
+                    generic
+                        .buy_exact_in(&PARAMS, remaining_coin_supply, amount)
+                        .unwrap(),
+                    curve
+                        .buy_exact_in(&PARAMS, remaining_coin_supply, amount)
+                        .unwrap()
+                );
+                assert_eq!(
+                    generic
+                        .sell_exact_out(&PARAMS, remaining_coin_supply, amount)
+                        .ok(),
+                    curve
+                        .sell_exact_out(&PARAMS, remaining_coin_supply, amount)
+                        .ok()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_buy_all_costs_about_85_sol() {
+        for kind in CurveKind::ALL {
+            let sol = kind.buy(&PARAMS, MAX_COIN_SUPPLY, SELLABLE_COINS).unwrap();
+            assert!(
+                (80e9 as u64..90e9 as u64).contains(&sol),
+                "{:?}: {}",
+                kind,
+                sol
+            );
+        }
+    }
+
+    #[test]
+    fn test_buy_exact_in_cost_not_exceeds_pay_amount() {
+        for kind in CurveKind::ALL {
+            for already_sold in [0, 2692001940000, 589359216751050, SELLABLE_COINS - 1] {
+                let remaining_coin_supply = MAX_COIN_SUPPLY - already_sold;
+                for pay_amount in [1, 1e6 as u64, 1e9 as u64, 10e9 as u64, 100e9 as u64] {
+                    let coin = kind
+                        .buy_exact_in(&PARAMS, remaining_coin_supply, pay_amount)
+                        .unwrap();
+                    assert!(coin <= SELLABLE_COINS - already_sold);
+                    assert!(kind.buy(&PARAMS, remaining_coin_supply, coin).unwrap() <= pay_amount);
+                    if coin < SELLABLE_COINS - already_sold {
+                        // one more coin would cost more than the pay amount
+                        assert!(
+                            kind.buy(&PARAMS, remaining_coin_supply, coin + 1).unwrap()
+                                > pay_amount
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sell_after_buy_not_exceeds_paid() {
+        for kind in CurveKind::ALL {
+            for already_sold in [0, 2692001940000, 589359216751050] {
+                let remaining_coin_supply = MAX_COIN_SUPPLY - already_sold;
+                for amount in [1, 1e6 as u64, 1e12 as u64, 100e12 as u64] {
+                    let paid = kind.buy(&PARAMS, remaining_coin_supply, amount).unwrap();
+                    let received = kind
+                        .sell(&PARAMS, remaining_coin_supply - amount, amount)
+                        .unwrap();
+                    assert!(received <= paid, "{:?}: {} > {}", kind, received, paid);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sell_exact_out_receives_at_least_amount() {
+        for kind in CurveKind::ALL {
+            let already_sold = 589359216751050;
+            let remaining_coin_supply = MAX_COIN_SUPPLY - already_sold;
+            for receive in [1, 1e6 as u64, 1e9 as u64, 10e9 as u64] {
+                let coin = kind
+                    .sell_exact_out(&PARAMS, remaining_coin_supply, receive)
+                    .unwrap();
+                assert!(kind.sell(&PARAMS, remaining_coin_supply, coin).unwrap() >= receive);
+            }
+            let all = kind.floor_sol_reserves(&PARAMS, already_sold) as u64;
+            assert_eq!(
+                kind.sell_exact_out(&PARAMS, remaining_coin_supply, all)
+                    .unwrap(),
+                already_sold
+            );
+            assert!(kind
+                .sell_exact_out(&PARAMS, remaining_coin_supply, all + 1)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_spot_price_increases() {
+        for kind in CurveKind::ALL {
+            let mut last = 0;
+            for already_sold in [0, 2692001940000, 589359216751050, SELLABLE_COINS] {
+                let price = kind
+                    .spot_price(&PARAMS, MAX_COIN_SUPPLY - already_sold)
+                    .unwrap();
+                assert!(price > last, "{:?}", kind);
+                last = price;
+            }
+            assert!(kind.spot_price(&PARAMS, REVERSE_COINS).unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn test_market_cap() {
+        let kind = CurveKind::ConstantProduct;
+        assert_eq!(
+            kind.market_cap(&PARAMS, MAX_COIN_SUPPLY).unwrap(),
+            27958993000
+        );
+        assert_eq!(
+            kind.market_cap(&PARAMS, REVERSE_COINS).unwrap(),
+            410880168000
+        );
+        for kind in CurveKind::ALL {
+            for remaining_coin_supply in [MAX_COIN_SUPPLY, REVERSE_COINS] {
+                let price = kind.spot_price(&PARAMS, remaining_coin_supply).unwrap();
+                assert_eq!(
+                    kind.market_cap(&PARAMS, remaining_coin_supply).unwrap() as u128,
+                    price * MAX_COIN_SUPPLY as u128 / coin_math::SPOT_PRICE_SCALE
+                );
+            }
+        }
+        let expensive = CurveParams {
+            virtual_sol_reverses: 2e18 as u64,
+            ..PARAMS
+        };
+        assert!(kind.market_cap(&expensive, MAX_COIN_SUPPLY).is_ok());
+        assert!(kind.market_cap(&expensive, REVERSE_COINS).is_err());
+    }
+
+    #[test]
+    fn test_progress_bps() {
+        assert_eq!(PARAMS.progress_bps(MAX_COIN_SUPPLY).unwrap(), 0);
+        assert_eq!(
+            PARAMS
+                .progress_bps(MAX_COIN_SUPPLY - SELLABLE_COINS / 2)
+                .unwrap(),
+            5000
+        );
+        assert_eq!(PARAMS.progress_bps(REVERSE_COINS + 1).unwrap(), 9999);
+        assert_eq!(PARAMS.progress_bps(REVERSE_COINS).unwrap(), BASIS_POINTS);
+        assert!(PARAMS.progress_bps(MAX_COIN_SUPPLY + 1).is_err());
+    }
+
+    #[test]
+    fn test_validate_params() {
+        assert!(PARAMS.validate().is_ok());
+        for params in [
+            CurveParams::default(),
+            CurveParams {
+                reverse_coins: 0,
+                ..PARAMS
+            },
+            CurveParams {
+                reverse_coins: MAX_COIN_SUPPLY,
+                ..PARAMS
+            },
+            CurveParams {
+                virtual_coin_reserves: 0,
+                ..PARAMS
+            },
+            CurveParams {
+                virtual_sol_reverses: 0,
+                ..PARAMS
+            },
+            CurveParams {
+                virtual_coin_reserves: u64::MAX,
+                ..PARAMS
+            },
+            CurveParams {
+                virtual_sol_reverses: u64::MAX,
+                ..PARAMS
+            },
+            CurveParams {
+                decimals: MAX_DECIMALS + 1,
+                ..PARAMS
+            },
+            CurveParams {
+                linear_offset: u64::MAX,
+                ..PARAMS
+            },
+            CurveParams {
+                linear_scale: 1,
+                ..PARAMS
+            },
+            CurveParams {
+                linear_scale: 0,
+                ..PARAMS
+            },
+            CurveParams {
+                exponential_doubling_coins: 0,
+                ..PARAMS
+            },
+            CurveParams {
+                exponential_sol_scale: 0,
+                ..PARAMS
+            },
+            // the price would double more than 64 times before the max supply
+            CurveParams {
+                exponential_doubling_coins: MAX_COIN_SUPPLY / 100,
+                ..PARAMS
+            },
+        ] {
+            assert!(params.validate().is_err(), "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn test_validate_hostile_params() {
+        // the reserves are checked without overflowing, whatever the other params
+        let params = CurveParams {
+            virtual_coin_reserves: u64::MAX - MAX_COIN_SUPPLY,
+            reverse_coins: u64::MAX,
+            ..PARAMS
+        };
+        assert!(CurveKind::ConstantProduct.validate(&params).is_err());
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_kind() {
+        for kind in CurveKind::ALL {
+            assert!(PARAMS.validate_kind(kind).is_ok());
+        }
+        // a curve whose params are not set is not offered, but does not fail the other curves
+        let constant_product_only = CurveParams {
+            linear_offset: 0,
+            linear_scale: 0,
+            exponential_doubling_coins: 0,
+            exponential_sol_scale: 0,
+            ..PARAMS
+        };
+        assert!(constant_product_only.validate().is_ok());
+        assert!(constant_product_only
+            .validate_kind(CurveKind::ConstantProduct)
+            .is_ok());
+        assert!(constant_product_only
+            .validate_kind(CurveKind::Linear)
+            .is_err());
+        assert!(constant_product_only
+            .validate_kind(CurveKind::Exponential)
+            .is_err());
+        let linear_only = CurveParams {
+            virtual_coin_reserves: 0,
+            virtual_sol_reverses: 0,
+            ..constant_product_only
+        };
+        assert!(linear_only.validate().is_ok());
+        assert!(linear_only
+            .validate_kind(CurveKind::ConstantProduct)
+            .is_err());
+        assert!(CurveParams {
+            linear_scale: PARAMS.linear_scale,
+            ..linear_only
+        }
+        .validate_kind(CurveKind::Linear)
+        .is_ok());
+    }
+
+    #[test]
+    fn test_curve_params_tune_the_price() {
+        let tuned = CurveParams {
+            linear_scale: PARAMS.linear_scale * 2,
+            exponential_sol_scale: PARAMS.exponential_sol_scale * 2,
+            ..PARAMS
+        };
+        assert_eq!(
+            CurveKind::Linear
+                .spot_price(&tuned, MAX_COIN_SUPPLY)
+                .unwrap()
+                * 2,
+            CurveKind::Linear
+                .spot_price(&PARAMS, MAX_COIN_SUPPLY)
+                .unwrap()
+        );
+        assert!(
+            (CurveKind::Exponential
+                .spot_price(&tuned, MAX_COIN_SUPPLY)
+                .unwrap()
+                .abs_diff(
+                    CurveKind::Exponential
+                        .spot_price(&PARAMS, MAX_COIN_SUPPLY)
+                        .unwrap()
+                        * 2
+                ))
+                <= 1
+        );
+        let slower = CurveParams {
+            exponential_doubling_coins: PARAMS.exponential_doubling_coins * 2,
+            ..PARAMS
+        };
+        assert!(
+            CurveKind::Exponential
+                .buy(&slower, MAX_COIN_SUPPLY, SELLABLE_COINS)
+                .unwrap()
+                < CurveKind::Exponential
+                    .buy(&PARAMS, MAX_COIN_SUPPLY, SELLABLE_COINS)
+                    .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_with_decimals() {
+        assert_eq!(PARAMS.with_decimals(DECIMALS).unwrap(), PARAMS);
+        let params = PARAMS.with_decimals(9).unwrap();
+        assert_eq!(params.max_coin_supply, MAX_COIN_SUPPLY * 1000);
+        assert_eq!(params.reverse_coins, REVERSE_COINS * 1000);
+        assert_eq!(params.virtual_sol_reverses, PARAMS.virtual_sol_reverses);
+        assert_eq!(
+            params.with_decimals(0).unwrap().max_coin_supply,
+            10e8 as u64
+        );
+        assert!(PARAMS.with_decimals(MAX_DECIMALS + 1).is_err());
+        // the supply can not be split below its smallest unit
+        let odd = CurveParams {
+            max_coin_supply: MAX_COIN_SUPPLY + 1,
+            ..PARAMS
+        };
+        assert!(odd.with_decimals(5).is_err());
+        assert!(odd.with_decimals(7).is_ok());
+    }
+
+    #[test]
+    fn test_decimals_keep_the_sol_price_of_a_whole_coin() {
+        for kind in CurveKind::ALL {
+            let sol = kind.buy(&PARAMS, MAX_COIN_SUPPLY, SELLABLE_COINS).unwrap();
+            let price = kind.spot_price(&PARAMS, MAX_COIN_SUPPLY).unwrap();
+            let coin = kind
+                .buy_exact_in(&PARAMS, MAX_COIN_SUPPLY, 1e9 as u64)
+                .unwrap();
+            for decimals in [0, 6, 9] {
+                let params = PARAMS.with_decimals(decimals).unwrap();
+                let unit = 10u128.pow(decimals as u32);
+                let sellable_coins = params.sellable_coins();
+                assert_eq!(
+                    kind.buy(&params, params.max_coin_supply, sellable_coins)
+                        .unwrap(),
+                    sol,
+                    "{:?} {}",
+                    kind,
+                    decimals
+                );
+                assert_eq!(
+                    kind.sell(&params, params.reverse_coins, sellable_coins)
+                        .unwrap(),
+                    sol
+                );
+                // the price of a whole coin, up to the rounding of the price of the smallest unit
+                let whole_coin_price =
+                    kind.spot_price(&params, params.max_coin_supply).unwrap() * unit;
+                assert!(
+                    whole_coin_price.abs_diff(price * 1e6 as u128) <= unit.max(1e6 as u128),
+                    "{:?} {}",
+                    kind,
+                    decimals
+                );
+                // the coins bought for 1 sol, up to the rounding of the smallest unit
+                let coin_with_decimals = kind
+                    .buy_exact_in(&params, params.max_coin_supply, 1e9 as u64)
+                    .unwrap() as u128;
+                assert!(
+                    (coin_with_decimals * 1e6 as u128).abs_diff(coin as u128 * unit)
+                        <= unit.max(1e6 as u128),
+                    "{:?} {}",
+                    kind,
+                    decimals
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_virtual_sol_reserves_scale_the_price() {
+        let doubled = CurveParams {
+            virtual_sol_reverses: PARAMS.virtual_sol_reverses * 2,
+            ..PARAMS
+        };
+        let kind = CurveKind::ConstantProduct;
+        assert_eq!(
+            kind.spot_price(&doubled, MAX_COIN_SUPPLY).unwrap(),
+            kind.spot_price(&PARAMS, MAX_COIN_SUPPLY).unwrap() * 2
+        );
+        let amount = 1e12 as u64;
+        let sol = kind.buy(&PARAMS, MAX_COIN_SUPPLY, amount).unwrap();
+        assert!(
+            kind.buy(&doubled, MAX_COIN_SUPPLY, amount)
+                .unwrap()
+                .abs_diff(sol * 2)
+                <= 1
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use anchor_lang::prelude::*;
+
+use super::{
+    coin_math::SPOT_PRICE_SCALE,
+    curve::{self, BondingCurve, CurveParams},
+};
+use crate::errors::Error;
+
+/// The scale of the fixed point numbers of [exp2] and [log2].
+pub const ONE: u128 = 1e18 as u128;
+/// `ln(2)` scaled by [ONE].
+pub const LN_2: u128 = 693_147_180_559_945_309;
+
+/// The curve whose price doubles every `exponential_doubling_coins` coins sold.
+///
+/// The sol reserves are `exponential_sol_scale * (2 ^ (already_sold / exponential_doubling_coins) - 1)`,
+/// see [CurveParams].
+pub struct ExponentialCurve;
+
+impl ExponentialCurve {
+    fn exponent(params: &CurveParams, already_sold: u64) -> u128 {
+        already_sold as u128 * ONE / params.exponential_doubling_coins as u128
+    }
+
+    /// Returns `exponential_sol_scale * 2 ^ (already_sold / exponential_doubling_coins)` scaled by [ONE],
+    /// saturated at `u128::MAX` past the coins checked by [BondingCurve::validate].
+    fn scaled_power(params: &CurveParams, already_sold: u64) -> u128 {
+        exp2(Self::exponent(params, already_sold)).map_or(u128::MAX, |power| {
+            power.saturating_mul(params.exponential_sol_scale as u128)
+        })
+    }
+}
+
+impl BondingCurve for ExponentialCurve {
+    fn is_offered(&self, params: &CurveParams) -> bool {
+        params.exponential_doubling_coins > 0 || params.exponential_sol_scale > 0
+    }
+
+    fn validate(&self, params: &CurveParams) -> Result<()> {
+        require!(
+            params.exponential_doubling_coins > 0 && params.exponential_sol_scale > 0,
+            Error::InvalidCurveParams
+        );
+        exp2(Self::exponent(params, params.max_coin_supply))
+            .and_then(|power| power.checked_mul(params.exponential_sol_scale as u128))
+            .ok_or(Error::InvalidCurveParams)?;
+        require!(
+            self.ceil_sol_reserves(params, params.sellable_coins()) <= u64::MAX as u128,
+            Error::InvalidCurveParams
+        );
+        Ok(())
+    }
+
+    fn sol_reserves(&self, params: &CurveParams, already_sold: u64) -> (u128, u128) {
+        (
+            Self::scaled_power(params, already_sold) - params.exponential_sol_scale as u128 * ONE,
+            ONE,
+        )
+    }
+
+    fn already_sold(&self, params: &CurveParams, sol_reserves: u128) -> u128 {
+        let exponent = log2(sol_reserves * ONE / params.exponential_sol_scale as u128 + ONE);
+        let estimate = exponent.saturating_mul(params.exponential_doubling_coins as u128) / ONE;
+        // the fixed point estimate is off by a few coins at most, step to the exact answer
+        let mut already_sold = estimate.min(params.max_coin_supply as u128) as u64;
+        let fits = |already_sold: u64| {
+            let (numerator, denominator) = self.sol_reserves(params, already_sold);
+            numerator <= sol_reserves * denominator
+        };
+        while already_sold < params.max_coin_supply && fits(already_sold + 1) {
+            already_sold += 1;
+        }
+        while already_sold > 0 && !fits(already_sold) {
+            already_sold -= 1;
+        }
+        already_sold as u128
+    }
+
+    fn spot_price(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u128> {
+        let already_sold = curve::already_sold(params, remaining_coin_supply)?;
+        // d/dx (scale * 2 ^ (x / doubling)) = scale * ln(2) * 2 ^ (x / doubling) / doubling
+        Ok(
+            Self::scaled_power(params, already_sold) / ONE * LN_2 / ONE * SPOT_PRICE_SCALE
+                / params.exponential_doubling_coins as u128,
+        )
+    }
+}
+
+/// Calculates `2 ^ y` for a fixed point `y` scaled by [ONE], rounded down, or `None` if it does not fit in `u128`.
+pub fn exp2(y: u128) -> Option<u128> {
+    // 2 ^ frac = e ^ (frac * ln(2)), summed by the taylor series
+    let z = y % ONE * LN_2 / ONE;
+    let mut sum = ONE;
+    let mut term = ONE;
+    let mut n = 1;
+    while term > 0 {
+        term = term * z / (n * ONE);
+        sum += term;
+        n += 1;
+    }
+    // the integer part shifts the sum, which must keep all its bits
+    let shift = y / ONE;
+    if shift > sum.leading_zeros() as u128 {
+        return None;
+    }
+    Some(sum << shift)
+}
+
+/// Calculates `log2(x)` for a fixed point `x >= ONE` scaled by [ONE], rounded down.
+pub fn log2(mut x: u128) -> u128 {
+    let mut result = 0;
+    while x >= 2 * ONE {
+        result += ONE;
+        x /= 2;
+    }
+    // each squaring shifts out one binary digit of the fraction
+    let mut delta = ONE / 2;
+    while delta > 0 {
+        x = x * x / ONE;
+        if x >= 2 * ONE {
+            result += delta;
+            x /= 2;
+        }
+        delta /= 2;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::curve::test_params::*;
+
+    use super::*;
+
+    #[test]
+    fn test_exp2() {
+        assert_eq!(exp2(0), Some(ONE));
+        assert_eq!(exp2(ONE), Some(2 * ONE));
+        assert_eq!(exp2(3 * ONE), Some(8 * ONE));
+        // sqrt(2) = 1.414213562373095048...
+        assert!(exp2(ONE / 2).unwrap().abs_diff(1_414_213_562_373_095_048) <= 10);
+        // ONE is about 2 ^ 59.8, so 2 ^ 69 * ONE is above u128::MAX
+        assert!(exp2(68 * ONE).is_some());
+        assert!(exp2(69 * ONE).is_none());
+        assert!(exp2(u128::MAX).is_none());
+    }
+
+    #[test]
+    fn test_log2() {
+        assert_eq!(log2(ONE), 0);
+        assert_eq!(log2(2 * ONE), ONE);
+        assert_eq!(log2(8 * ONE), 3 * ONE);
+        for y in [1, ONE / 3, ONE / 2, 5 * ONE / 2] {
+            assert!(log2(exp2(y).unwrap()).abs_diff(y) <= 100, "{}", y);
+        }
+    }
+
+    #[test]
+    fn test_buy_already_sold_is_0_and_buy_all() {
+        let sol = ExponentialCurve
+            .buy(&PARAMS, MAX_COIN_SUPPLY, SELLABLE_COINS)
+            .unwrap();
+        assert_eq!(sol, 87731528279);
+        assert_eq!(
+            ExponentialCurve
+                .buy_exact_in(&PARAMS, MAX_COIN_SUPPLY, sol)
+                .unwrap(),
+            SELLABLE_COINS
+        );
+        assert_eq!(
+            ExponentialCurve
+                .sell(&PARAMS, REVERSE_COINS, SELLABLE_COINS)
+                .unwrap(),
+            sol
+        );
+    }
+
+    #[test]
+    fn test_already_sold_is_the_largest_not_exceeding_sol_reserves() {
+        for sol in [0, 1, 1e9 as u128, 50e9 as u128] {
+            let coin = ExponentialCurve.already_sold(&PARAMS, sol) as u64;
+            assert!(ExponentialCurve.floor_sol_reserves(&PARAMS, coin) <= sol);
+            let (numerator, denominator) = ExponentialCurve.sol_reserves(&PARAMS, coin + 1);
+            assert!(numerator > sol * denominator);
+        }
+        assert_eq!(
+            ExponentialCurve.already_sold(&PARAMS, u64::MAX as u128),
+            MAX_COIN_SUPPLY as u128
+        );
+    }
+
+    #[test]
+    fn test_spot_price() {
+        assert_eq!(
+            ExponentialCurve
+                .spot_price(&PARAMS, MAX_COIN_SUPPLY)
+                .unwrap(),
+            20794415
+        );
+        assert_eq!(
+            ExponentialCurve.spot_price(&PARAMS, REVERSE_COINS).unwrap(),
+            324848722
+        );
+    }
+}
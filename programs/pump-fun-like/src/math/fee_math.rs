@@ -1,13 +1,49 @@
 use crate::constants::FEE_RATE_BASIS_POINT;
+use crate::errors::Error;
 
 /// Calculates the amount of fee to pay when buying coin.
-pub fn buy(pay_amount_without_fee: u64, maker_fee_rate: u32) -> u64 {
-    (pay_amount_without_fee as u128 * maker_fee_rate as u128 / FEE_RATE_BASIS_POINT as u128) as u64
+pub fn buy(pay_amount_without_fee: u64, maker_fee_rate: u32) -> Result<u64, Error> {
+    fee(pay_amount_without_fee, maker_fee_rate)
 }
 
 /// Calculates the amount of fee to pay when selling coin.
-pub fn sell(sol_amount: u64, taker_fee_rate: u32) -> u64 {
-    (sol_amount as u128 * taker_fee_rate as u128 / FEE_RATE_BASIS_POINT as u128) as u64
+pub fn sell(sol_amount: u64, taker_fee_rate: u32) -> Result<u64, Error> {
+    fee(sol_amount, taker_fee_rate)
+}
+
+/// Calculates the maximum amount of sol to pay (not including the fee) when buying coin,
+/// so that the amount plus the fee does not exceed `total_pay`.
+pub fn buy_pay_amount(total_pay: u64, maker_fee_rate: u32) -> Result<u64, Error> {
+    // pay_amount + pay_amount * maker_fee_rate / FEE_RATE_BASIS_POINT <= total_pay
+    let pay_amount = (total_pay as u128 * FEE_RATE_BASIS_POINT as u128
+        / (FEE_RATE_BASIS_POINT as u128 + maker_fee_rate as u128)) as u64;
+    // The fee is rounded down, so one more lamport may still be affordable.
+    if pay_amount < total_pay {
+        let next = pay_amount + 1;
+        let next_total = next
+            .checked_add(buy(next, maker_fee_rate)?)
+            .ok_or(Error::MathOverflow)?;
+        if next_total <= total_pay {
+            return Ok(next);
+        }
+    }
+    Ok(pay_amount)
+}
+
+fn fee(amount: u64, fee_rate: u32) -> Result<u64, Error> {
+    u64::try_from(amount as u128 * fee_rate as u128 / FEE_RATE_BASIS_POINT as u128)
+        .map_err(|_| Error::MathOverflow)
+}
+
+/// Calculates the launch tax rate of a buy `elapsed_slots` after the coin is created.
+///
+/// The rate decays linearly from `launch_tax_rate` at the creation slot to 0 after `launch_tax_slots`.
+pub fn launch_tax_rate(launch_tax_rate: u32, launch_tax_slots: u64, elapsed_slots: u64) -> u32 {
+    if elapsed_slots >= launch_tax_slots {
+        return 0;
+    }
+    (launch_tax_rate as u128 * (launch_tax_slots - elapsed_slots) as u128
+        / launch_tax_slots as u128) as u32
 }
 
 #[cfg(test)]
@@ -16,39 +52,131 @@ mod tests {
 
     #[test]
     pub fn test_buy_maker_fee_rate_is_0() {
-        assert_eq!(buy(0, 0), 0);
-        assert_eq!(buy(1, 0), 0);
-        assert_eq!(buy(1e9 as u64, 0), 0);
+        assert_eq!(buy(0, 0).unwrap(), 0);
+        assert_eq!(buy(1, 0).unwrap(), 0);
+        assert_eq!(buy(1e9 as u64, 0).unwrap(), 0);
     }
 
     #[test]
+    #[allow(clippy::unnecessary_cast)]
     pub fn test_buy_maker_fee_rate_is_0_dot_005() {
         assert_eq!(
-            buy(1e9 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32),
+            buy(1e9 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32).unwrap(),
             5000000
         );
         assert_eq!(
-            buy(333 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32),
+            buy(333 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32).unwrap(),
             1
         );
     }
 
     #[test]
     pub fn test_sell_maker_fee_rate_is_0() {
-        assert_eq!(sell(0, 0), 0);
-        assert_eq!(sell(1, 0), 0);
-        assert_eq!(sell(1e9 as u64, 0), 0);
+        assert_eq!(sell(0, 0).unwrap(), 0);
+        assert_eq!(sell(1, 0).unwrap(), 0);
+        assert_eq!(sell(1e9 as u64, 0).unwrap(), 0);
     }
 
     #[test]
+    #[allow(clippy::unnecessary_cast)]
     pub fn test_sell_maker_fee_rate_is_0_dot_005() {
         assert_eq!(
-            sell(1e9 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32),
+            sell(1e9 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32).unwrap(),
             5000000
         );
         assert_eq!(
-            sell(333 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32),
+            sell(333 as u64, (0.005 * FEE_RATE_BASIS_POINT as f64) as u32).unwrap(),
             1
         );
     }
+    #[test]
+    pub fn test_buy_pay_amount_maker_fee_rate_is_0() {
+        assert_eq!(buy_pay_amount(0, 0).unwrap(), 0);
+        assert_eq!(buy_pay_amount(1, 0).unwrap(), 1);
+        assert_eq!(buy_pay_amount(1e9 as u64, 0).unwrap(), 1e9 as u64);
+    }
+
+    #[test]
+    pub fn test_buy_pay_amount_maker_fee_rate_is_0_dot_01() {
+        let maker_fee_rate = FEE_RATE_BASIS_POINT / 100;
+        assert_eq!(
+            buy_pay_amount(1e9 as u64, maker_fee_rate).unwrap(),
+            990099010
+        );
+        assert_eq!(buy(990099010, maker_fee_rate).unwrap(), 9900990);
+        // the fee of 100 is 1, so 100 + 1 = 101 is affordable
+        assert_eq!(buy_pay_amount(101, maker_fee_rate).unwrap(), 100);
+        // the fee of 99 is rounded down to 0
+        assert_eq!(buy_pay_amount(99, maker_fee_rate).unwrap(), 99);
+        assert_eq!(buy_pay_amount(100, maker_fee_rate).unwrap(), 99);
+    }
+
+    #[test]
+    pub fn test_buy_pay_amount_is_maximum() {
+        for maker_fee_rate in [1, FEE_RATE_BASIS_POINT / 200, FEE_RATE_BASIS_POINT / 3] {
+            for total_pay in [1, 2, 99, 100, 101, 333, 1e9 as u64 + 7, u64::MAX / 2] {
+                let pay_amount = buy_pay_amount(total_pay, maker_fee_rate).unwrap();
+                assert!(pay_amount + buy(pay_amount, maker_fee_rate).unwrap() <= total_pay);
+                let next = pay_amount + 1;
+                assert!(next + buy(next, maker_fee_rate).unwrap() > total_pay);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_fee_overflows() {
+        assert_eq!(buy(u64::MAX, FEE_RATE_BASIS_POINT).unwrap(), u64::MAX);
+        assert!(matches!(
+            buy(u64::MAX, FEE_RATE_BASIS_POINT + 1),
+            Err(Error::MathOverflow)
+        ));
+        assert!(matches!(
+            sell(u64::MAX, FEE_RATE_BASIS_POINT * 2),
+            Err(Error::MathOverflow)
+        ));
+    }
+
+    #[test]
+    pub fn test_launch_tax_rate_is_0() {
+        assert_eq!(launch_tax_rate(0, 10, 0), 0);
+        assert_eq!(launch_tax_rate(FEE_RATE_BASIS_POINT / 2, 0, 0), 0);
+        assert_eq!(launch_tax_rate(FEE_RATE_BASIS_POINT / 2, 0, 1), 0);
+    }
+
+    #[test]
+    pub fn test_launch_tax_rate_decays_from_50_percent_over_10_slots() {
+        let rate = FEE_RATE_BASIS_POINT / 2;
+        let expected = [
+            50000000, 45000000, 40000000, 35000000, 30000000, 25000000, 20000000, 15000000,
+            10000000, 5000000, 0, 0,
+        ];
+        for (elapsed_slots, expected) in expected.into_iter().enumerate() {
+            assert_eq!(launch_tax_rate(rate, 10, elapsed_slots as u64), expected);
+        }
+        assert_eq!(launch_tax_rate(rate, 10, u64::MAX), 0);
+    }
+
+    #[test]
+    pub fn test_launch_tax_rate_is_rounded_down() {
+        // 1% over 3 slots
+        let rate = FEE_RATE_BASIS_POINT / 100;
+        assert_eq!(launch_tax_rate(rate, 3, 0), 1000000);
+        assert_eq!(launch_tax_rate(rate, 3, 1), 666666);
+        assert_eq!(launch_tax_rate(rate, 3, 2), 333333);
+        assert_eq!(launch_tax_rate(rate, 3, 3), 0);
+    }
+
+    #[test]
+    pub fn test_launch_tax_fee_at_each_slot() {
+        // pays 1 sol with a 1% maker fee and a 50% launch tax over 4 slots
+        let maker_fee_rate = FEE_RATE_BASIS_POINT / 100;
+        let expected = [510000000, 385000000, 260000000, 135000000, 10000000];
+        for (elapsed_slots, expected) in expected.into_iter().enumerate() {
+            let tax_rate = launch_tax_rate(FEE_RATE_BASIS_POINT / 2, 4, elapsed_slots as u64);
+            assert_eq!(
+                buy(1e9 as u64, maker_fee_rate + tax_rate).unwrap(),
+                expected
+            );
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+
+use super::{
+    coin_math::SPOT_PRICE_SCALE,
+    curve::{self, BondingCurve, CurveParams},
+    math,
+};
+use crate::errors::Error;
+
+/// The curve whose price grows linearly with the coins already sold.
+///
+/// The sol reserves are `already_sold * (already_sold + linear_offset) / linear_scale`, see [CurveParams].
+pub struct LinearCurve;
+
+impl BondingCurve for LinearCurve {
+    fn is_offered(&self, params: &CurveParams) -> bool {
+        params.linear_offset > 0 || params.linear_scale > 0
+    }
+
+    fn validate(&self, params: &CurveParams) -> Result<()> {
+        require!(params.linear_scale > 0, Error::InvalidCurveParams);
+        // `already_sold` squares the coins with the half offset, on top of the sol reserves times the scale
+        let top = params.max_coin_supply as u128 + params.linear_offset as u128;
+        top.checked_mul(top)
+            .and_then(|square| square.checked_add(params.linear_scale))
+            .ok_or(Error::InvalidCurveParams)?;
+        require!(
+            self.ceil_sol_reserves(params, params.sellable_coins()) <= u64::MAX as u128,
+            Error::InvalidCurveParams
+        );
+        Ok(())
+    }
+
+    fn sol_reserves(&self, params: &CurveParams, already_sold: u64) -> (u128, u128) {
+        (
+            (already_sold as u128)
+                .saturating_mul(already_sold as u128 + params.linear_offset as u128),
+            params.linear_scale,
+        )
+    }
+
+    fn already_sold(&self, params: &CurveParams, sol_reserves: u128) -> u128 {
+        // past the max coin supply the square below could overflow
+        if sol_reserves >= self.ceil_sol_reserves(params, params.max_coin_supply) {
+            return params.max_coin_supply as u128;
+        }
+        // x * (x + offset) <= scale * sol  <=>  (x + offset / 2)^2 <= (offset / 2)^2 + scale * sol
+        let half_offset = params.linear_offset as u128 / 2;
+        math::sqrt(half_offset * half_offset + params.linear_scale * sol_reserves) - half_offset
+    }
+
+    fn spot_price(&self, params: &CurveParams, remaining_coin_supply: u64) -> Result<u128> {
+        let already_sold = curve::already_sold(params, remaining_coin_supply)?;
+        Ok(
+            (2 * already_sold as u128 + params.linear_offset as u128) * SPOT_PRICE_SCALE
+                / params.linear_scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::curve::test_params::*;
+
+    use super::*;
+
+    #[test]
+    fn test_buy_already_sold_is_0_and_buy_all() {
+        let sol = LinearCurve
+            .buy(&PARAMS, MAX_COIN_SUPPLY, SELLABLE_COINS)
+            .unwrap();
+        assert_eq!(sol, 85107561000);
+        assert_eq!(
+            LinearCurve
+                .buy_exact_in(&PARAMS, MAX_COIN_SUPPLY, sol)
+                .unwrap(),
+            SELLABLE_COINS
+        );
+        assert_eq!(
+            LinearCurve
+                .sell(&PARAMS, REVERSE_COINS, SELLABLE_COINS)
+                .unwrap(),
+            sol
+        );
+    }
+
+    #[test]
+    fn test_buy_already_sold_is_0_and_pay_1e9() {
+        let coin = LinearCurve
+            .buy_exact_in(&PARAMS, MAX_COIN_SUPPLY, 1e9 as u64)
+            .unwrap();
+        assert_eq!(coin, 32046505340852);
+    }
+
+    #[test]
+    fn test_already_sold_is_the_largest_not_exceeding_sol_reserves() {
+        for sol in [0, 1, 1e9 as u128, 85107561000] {
+            let coin = LinearCurve.already_sold(&PARAMS, sol) as u64;
+            assert!(LinearCurve.floor_sol_reserves(&PARAMS, coin) <= sol);
+            let (numerator, denominator) = LinearCurve.sol_reserves(&PARAMS, coin + 1);
+            assert!(numerator > sol * denominator);
+        }
+        assert_eq!(
+            LinearCurve.already_sold(&PARAMS, u64::MAX as u128),
+            MAX_COIN_SUPPLY as u128
+        );
+    }
+
+    #[test]
+    fn test_spot_price() {
+        assert_eq!(
+            LinearCurve.spot_price(&PARAMS, MAX_COIN_SUPPLY).unwrap(),
+            28000000
+        );
+        assert_eq!(
+            LinearCurve.spot_price(&PARAMS, REVERSE_COINS).unwrap(),
+            186620000
+        );
+    }
+}
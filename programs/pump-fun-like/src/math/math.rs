@@ -6,3 +6,34 @@ pub fn ceil_div(a: u128, b: u128) -> u128 {
         c + 1
     }
 }
+
+/// Returns the square root of `a`, rounded down.
+pub fn sqrt(a: u128) -> u128 {
+    if a < 2 {
+        return a;
+    }
+    // start from a power of two not less than the root, Newton's method then decreases to the floor
+    let mut x = 1u128 << ((128 - a.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + a / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(sqrt(0), 0);
+        assert_eq!(sqrt(1), 1);
+        assert_eq!(sqrt(8), 2);
+        assert_eq!(sqrt(9), 3);
+        assert_eq!(sqrt(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+        assert_eq!(sqrt(u128::MAX), u64::MAX as u128);
+    }
+}
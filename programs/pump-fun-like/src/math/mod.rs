@@ -1,4 +1,9 @@
 pub mod coin_math;
+pub mod curve;
+pub mod exponential_math;
 pub mod fee_math;
+pub mod linear_math;
+#[allow(clippy::module_inception)]
 pub mod math;
+pub mod pool_math;
 pub mod sol_math;
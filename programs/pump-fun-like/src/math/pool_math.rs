@@ -0,0 +1,55 @@
+/// Calculates the amount of output of a constant product (x * y = k) swap.
+///
+/// # Parameters
+/// - `reserve_in`: The reserve of the input side before the swap.
+/// - `reserve_out`: The reserve of the output side before the swap.
+/// - `amount_in`: The amount of input.
+///
+/// # Returns
+/// The amount of output, rounded down so that `k` never decreases.
+pub fn swap(reserve_in: u64, reserve_out: u64, amount_in: u64) -> u64 {
+    if amount_in == 0 {
+        return 0;
+    }
+    let numerator = reserve_out as u128 * amount_in as u128;
+    let denominator = reserve_in as u128 + amount_in as u128;
+    (numerator / denominator) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::curve::test_params::REVERSE_COINS;
+
+    use super::*;
+
+    #[test]
+    fn test_swap_amount_in_is_0() {
+        assert_eq!(swap(0, REVERSE_COINS, 0), 0);
+        assert_eq!(swap(85005359057, REVERSE_COINS, 0), 0);
+    }
+
+    #[test]
+    fn test_swap_sol_for_coin() {
+        let coin = swap(85005359057, REVERSE_COINS, 1e9 as u64);
+        assert_eq!(coin, 2405664045456);
+    }
+
+    #[test]
+    fn test_swap_coin_for_sol() {
+        let sol = swap(REVERSE_COINS, 85005359057, 2405664045456);
+        assert_eq!(sol, 977012910);
+    }
+
+    #[test]
+    fn test_swap_k_never_decreases() {
+        let (sol_reserve, coin_reserve) = (85005359057u64, REVERSE_COINS);
+        let k = sol_reserve as u128 * coin_reserve as u128;
+        for amount_in in [1, 333, 1e6 as u64, 1e9 as u64, 100e9 as u64] {
+            let coin = swap(sol_reserve, coin_reserve, amount_in);
+            assert!((sol_reserve + amount_in) as u128 * (coin_reserve - coin) as u128 >= k);
+
+            let sol = swap(coin_reserve, sol_reserve, amount_in);
+            assert!((sol_reserve - sol) as u128 * (coin_reserve + amount_in) as u128 >= k);
+        }
+    }
+}
@@ -1,119 +1,153 @@
-use crate::{
-    constants::{MAX_COIN_SUPPLY, MAX_VIRTUAL_COIN_RESERVES, SELLABLE_COINS, VIRTUAL_SOL_REVERSES},
-    errors::Error,
-};
+use super::curve::CurveParams;
+use crate::errors::Error;
 
 /// Calculates the amount of coin to be bought.
 ///
 /// # Parameters
-/// - `remaining_coin_supply`: The remaining supply of the coin, including the [crate::constants::REVERSE_COINS].
+/// - `params`: The parameters of the bonding curve.
+/// - `remaining_coin_supply`: The remaining supply of the coin, including the `reverse_coins` of the `params`.
 /// - `amount`: The amount of sol to be paid.
 ///
 /// # Returns
 /// The amount of coin to be bought.
-pub fn buy(remaining_coin_supply: u64, amount: u64) -> u64 {
-    let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
-    let numerator: u128 = already_sold as u128 * VIRTUAL_SOL_REVERSES as u128;
-    let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold;
-    let sol_before = numerator / denominator as u128; // why not use ceil_div here? see why_round_down.png
-
-    let sol_after = sol_before + amount as u128;
-    let numerator = sol_after * MAX_VIRTUAL_COIN_RESERVES as u128;
-    let denominator = VIRTUAL_SOL_REVERSES as u128 + sol_after;
-    let coin_after = numerator / denominator;
+pub fn buy(params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64, Error> {
+    let already_sold = params
+        .max_coin_supply
+        .checked_sub(remaining_coin_supply)
+        .ok_or(Error::MathUnderflow)?;
+    let sol_before = sol_reserves(params, already_sold)?; // why not use ceil_div here? see why_round_down.png
+
+    let sol_after = sol_before
+        .checked_add(amount as u128)
+        .ok_or(Error::MathOverflow)?;
+    let coin_after = coin_reserves(params, sol_after)?;
+    let sellable_coins = params.sellable_coins() as u128;
     if coin_after <= already_sold as u128 {
-        0
-    } else if coin_after >= SELLABLE_COINS as u128 {
-        (SELLABLE_COINS - already_sold) as u64
+        Ok(0)
+    } else if coin_after >= sellable_coins {
+        params
+            .sellable_coins()
+            .checked_sub(already_sold)
+            .ok_or(Error::MathUnderflow)
     } else {
-        (coin_after - already_sold as u128) as u64
+        Ok((coin_after - already_sold as u128) as u64)
     }
 }
 
 /// Calculates the amount of coin to be sold.
 ///
 /// # Parameters
-/// - `remaining_supply`: The remaining supply of the coin, including the [crate::constants::REVERSE_COINS].
+/// - `params`: The parameters of the bonding curve.
+/// - `remaining_supply`: The remaining supply of the coin, including the `reverse_coins` of the `params`.
 /// - `amount`: The amount of sol to be received.
 ///
 /// # Returns
 /// - The amount of coin to be sold.
-pub fn sell(remaining_coin_supply: u64, amount: u64) -> Result<u64, Error> {
-    let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
-    let numerator: u128 = already_sold as u128 * VIRTUAL_SOL_REVERSES as u128;
-    let denominator = MAX_VIRTUAL_COIN_RESERVES - already_sold;
-    let sol_before = numerator / denominator as u128; // why not use ceil_div here? see why_round_down_for_sell.png
-
-    if (sol_before as u64) < amount {
-        return Err(Error::ExactOutTooLarge.into());
-    } else if (sol_before as u64) == amount {
+pub fn sell(params: &CurveParams, remaining_coin_supply: u64, amount: u64) -> Result<u64, Error> {
+    let already_sold = params
+        .max_coin_supply
+        .checked_sub(remaining_coin_supply)
+        .ok_or(Error::MathUnderflow)?;
+    let sol_before = sol_reserves(params, already_sold)?; // why not use ceil_div here? see why_round_down_for_sell.png
+
+    if sol_before < amount as u128 {
+        return Err(Error::ExactOutTooLarge);
+    } else if sol_before == amount as u128 {
         // special case: if sol_before is less than amount, it means all sold coins need to be returned
         return Ok(already_sold);
     }
 
     let sol_after = sol_before - amount as u128;
-    let numerator = sol_after * MAX_VIRTUAL_COIN_RESERVES as u128;
-    let denominator = VIRTUAL_SOL_REVERSES as u128 + sol_after;
-    let coin_after = numerator / denominator;
+    let coin_after = coin_reserves(params, sol_after)?;
     if coin_after >= already_sold as u128 {
-        Err(Error::UnexpectExactOutput.into())
+        Err(Error::UnexpectExactOutput)
     } else {
-        Ok((already_sold - coin_after as u64) as u64)
+        Ok(already_sold - coin_after as u64)
     }
 }
 
+/// Returns the sol reserves after `already_sold` coins are sold, rounded down.
+fn sol_reserves(params: &CurveParams, already_sold: u64) -> Result<u128, Error> {
+    let numerator = (already_sold as u128)
+        .checked_mul(params.virtual_sol_reverses as u128)
+        .ok_or(Error::MathOverflow)?;
+    let denominator = params
+        .max_virtual_coin_reserves()
+        .checked_sub(already_sold)
+        .filter(|denominator| *denominator > 0)
+        .ok_or(Error::MathUnderflow)?;
+    Ok(numerator / denominator as u128)
+}
+
+/// Returns the coins already sold once the sol reserves reach `sol_reserves`, rounded down.
+fn coin_reserves(params: &CurveParams, sol_reserves: u128) -> Result<u128, Error> {
+    let numerator = sol_reserves
+        .checked_mul(params.max_virtual_coin_reserves() as u128)
+        .ok_or(Error::MathOverflow)?;
+    let denominator = (params.virtual_sol_reverses as u128)
+        .checked_add(sol_reserves)
+        .ok_or(Error::MathOverflow)?;
+    Ok(numerator / denominator)
+}
+
 #[cfg(test)]
 mod tests {
     use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
+    use proptest::prelude::*;
 
-    use crate::{constants::REVERSE_COINS, math::coin_math};
+    use crate::math::{coin_math, curve::test_params::*};
 
     use super::*;
 
     #[test]
     pub fn test_buy_already_sold_is_0_and_buy_all() {
-        let coin = buy(MAX_COIN_SUPPLY, 85005359057);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, 85005359057).unwrap();
         assert_eq!(coin, 793100000000000);
     }
 
     #[test]
     pub fn test_buy_already_sold_is_0_and_buy_all_and_pay_amount_is_too_high() {
-        let coin = buy(MAX_COIN_SUPPLY, 85005359057 + LAMPORTS_PER_SOL);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, 85005359057 + LAMPORTS_PER_SOL).unwrap();
         assert_eq!(coin, 793100000000000);
     }
 
     #[test]
     pub fn test_buy_already_sold_is_0_and_pay_1() {
-        let coin = buy(MAX_COIN_SUPPLY, 1);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, 1).unwrap();
         assert_eq!(coin, (0.035766 * 1e6) as u64);
     }
 
     #[test]
     pub fn test_buy_already_sold_is_0_and_pay_1e8() {
-        let coin = buy(MAX_COIN_SUPPLY, 1e8 as u64);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, 1e8 as u64).unwrap();
         assert_eq!(coin, (3564784.053156 * 1e6) as u64);
     }
 
     #[test]
     pub fn test_buy_already_sold_is_0_and_pay_1e9() {
-        let coin = buy(MAX_COIN_SUPPLY, 1e9 as u64);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, 1e9 as u64).unwrap();
         assert_eq!(coin, 34612903225806);
     }
 
     #[test]
     pub fn test_buy_already_sold_is_0_and_pay_3333333333() {
-        let coin = buy(MAX_COIN_SUPPLY, 3333333333);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, 3333333333).unwrap();
         assert_eq!(coin, 107299999990342);
     }
 
     #[test]
     pub fn test_buy_already_sold_is_2692001940000_and_pay_10e9() {
         let already_sold = 2692001940000;
-        let coin = buy(MAX_COIN_SUPPLY - already_sold, 10e9 as u64);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY - already_sold, 10e9 as u64).unwrap();
 
-        let buy_sol = coin_math::buy(MAX_COIN_SUPPLY, already_sold + coin);
-        let sell_sol = coin_math::sell(MAX_COIN_SUPPLY - already_sold - coin, already_sold + coin);
-        let buy_sol_already_sold = coin_math::buy(MAX_COIN_SUPPLY, already_sold);
+        let buy_sol = coin_math::buy(&PARAMS, MAX_COIN_SUPPLY, already_sold + coin).unwrap();
+        let sell_sol = coin_math::sell(
+            &PARAMS,
+            MAX_COIN_SUPPLY - already_sold - coin,
+            already_sold + coin,
+        )
+        .unwrap();
+        let buy_sol_already_sold = coin_math::buy(&PARAMS, MAX_COIN_SUPPLY, already_sold).unwrap();
         assert!(buy_sol >= sell_sol);
         assert!(buy_sol_already_sold + 10e9 as u64 >= sell_sol);
         println!(
@@ -127,22 +161,53 @@ mod tests {
 
     #[test]
     pub fn test_sell_already_sold_is_all() {
-        let sol = coin_math::buy(MAX_COIN_SUPPLY, MAX_COIN_SUPPLY - REVERSE_COINS);
+        let sol =
+            coin_math::buy(&PARAMS, MAX_COIN_SUPPLY, MAX_COIN_SUPPLY - REVERSE_COINS).unwrap();
         println!("sol: {}", sol);
-        let coin = buy(MAX_COIN_SUPPLY, sol);
+        let coin = buy(&PARAMS, MAX_COIN_SUPPLY, sol).unwrap();
         assert_eq!(coin, MAX_COIN_SUPPLY - REVERSE_COINS);
 
-        let coin_recover_buy_sell = sell(REVERSE_COINS, sol - 1).unwrap();
+        let coin_recover_buy_sell = sell(&PARAMS, REVERSE_COINS, sol - 1).unwrap();
         println!("coin_recover_buy_sell: {}", coin_recover_buy_sell);
         assert_eq!(coin_recover_buy_sell, coin);
     }
 
+    #[test]
+    pub fn test_buy_clamped_and_cost_not_exceeds_pay_amount() {
+        for already_sold in [0, 2692001940000, 589359216751050, SELLABLE_COINS - 1] {
+            let remaining_coin_supply = MAX_COIN_SUPPLY - already_sold;
+            let cost = coin_math::buy(
+                &PARAMS,
+                remaining_coin_supply,
+                SELLABLE_COINS - already_sold,
+            )
+            .unwrap();
+            for pay_amount in [cost, cost + 1, cost + LAMPORTS_PER_SOL] {
+                let coin = buy(&PARAMS, remaining_coin_supply, pay_amount).unwrap();
+                assert_eq!(coin, SELLABLE_COINS - already_sold);
+                assert!(
+                    coin_math::buy(&PARAMS, remaining_coin_supply, coin).unwrap() <= pay_amount
+                );
+                assert_eq!(
+                    coin_math::buy(&PARAMS, remaining_coin_supply, coin).unwrap(),
+                    cost
+                );
+            }
+        }
+    }
+
     #[test]
     pub fn test_pump_fun() {
         let real_token_reserves: u64 = 589359216751050;
-        let coin = buy(real_token_reserves + REVERSE_COINS, (0.5 * 1e9) as u64);
+        let coin = buy(
+            &PARAMS,
+            real_token_reserves + REVERSE_COINS,
+            (0.5 * 1e9) as u64,
+        )
+        .unwrap();
         assert_eq!(coin, 11580385658285);
         let coin_recover = sell(
+            &PARAMS,
             real_token_reserves + REVERSE_COINS - coin,
             (0.5 * 1e9) as u64,
         )
@@ -150,9 +215,15 @@ mod tests {
         assert!(coin_recover >= coin);
         println!("diff: {}", coin_recover - coin);
 
-        let coin = buy(real_token_reserves + REVERSE_COINS, (1.0 * 1e9) as u64);
+        let coin = buy(
+            &PARAMS,
+            real_token_reserves + REVERSE_COINS,
+            (1.0 * 1e9) as u64,
+        )
+        .unwrap();
         assert_eq!(coin, 22856276991103);
         let coin_recover = sell(
+            &PARAMS,
             real_token_reserves + REVERSE_COINS - coin,
             (1.0 * 1e9) as u64,
         )
@@ -160,9 +231,15 @@ mod tests {
         assert!(coin_recover >= coin);
         println!("diff: {}", coin_recover - coin);
 
-        let coin = buy(real_token_reserves + REVERSE_COINS, (23.33333 * 1e9) as u64);
+        let coin = buy(
+            &PARAMS,
+            real_token_reserves + REVERSE_COINS,
+            (23.33333 * 1e9) as u64,
+        )
+        .unwrap();
         assert_eq!(coin, 336001966735479);
         let coin_recover = sell(
+            &PARAMS,
             real_token_reserves + REVERSE_COINS - coin,
             (23.33333 * 1e9) as u64,
         )
@@ -170,4 +247,62 @@ mod tests {
         assert!(coin_recover >= coin);
         println!("diff: {}", coin_recover - coin);
     }
+
+    /// The accumulated sol of the bonding curve, rounded down like [buy] and [sell] do.
+    fn sol_reserves(remaining_coin_supply: u64) -> u64 {
+        let already_sold = MAX_COIN_SUPPLY - remaining_coin_supply;
+        (already_sold as u128 * PARAMS.virtual_sol_reverses as u128
+            / (PARAMS.max_virtual_coin_reserves() - already_sold) as u128) as u64
+    }
+
+    /// A remaining coin supply anywhere on the bonding curve, and an amount of coin available to buy from it.
+    fn buyable() -> impl Strategy<Value = (u64, u64)> {
+        (REVERSE_COINS..=MAX_COIN_SUPPLY)
+            .prop_flat_map(|remaining| (Just(remaining), 0..=remaining - REVERSE_COINS))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_buy_inverts_coin_math_buy((remaining_coin_supply, amount) in buyable()) {
+            let cost = coin_math::buy(&PARAMS, remaining_coin_supply, amount).unwrap();
+            let coin = buy(&PARAMS, remaining_coin_supply, cost).unwrap();
+            // paying the cost buys at least the amount, and any extra coins are free of rounding
+            prop_assert!(coin >= amount);
+            prop_assert_eq!(coin_math::buy(&PARAMS, remaining_coin_supply, coin).unwrap(), cost);
+        }
+
+        #[test]
+        fn prop_buy_is_the_most_coins_for_the_pay_amount(
+            remaining_coin_supply in REVERSE_COINS..=MAX_COIN_SUPPLY,
+            pay_amount in prop_oneof![0..=100 * LAMPORTS_PER_SOL, any::<u64>()],
+        ) {
+            let coin = buy(&PARAMS, remaining_coin_supply, pay_amount).unwrap();
+            let available = remaining_coin_supply - REVERSE_COINS;
+            prop_assert!(coin <= available);
+            prop_assert!(coin_math::buy(&PARAMS, remaining_coin_supply, coin).unwrap() <= pay_amount);
+            if coin < available {
+                prop_assert!(coin_math::buy(&PARAMS, remaining_coin_supply, coin + 1).unwrap() > pay_amount);
+            }
+        }
+
+        #[test]
+        fn prop_sell_receives_at_least_the_amount(
+            (remaining_coin_supply, amount) in (REVERSE_COINS..MAX_COIN_SUPPLY)
+                .prop_filter("nothing to receive", |remaining| sol_reserves(*remaining) > 0)
+                .prop_flat_map(|remaining| (Just(remaining), 1..=sol_reserves(remaining)))
+        ) {
+            let coin = sell(&PARAMS, remaining_coin_supply, amount).unwrap();
+            prop_assert!(coin <= MAX_COIN_SUPPLY - remaining_coin_supply);
+            prop_assert!(coin_math::sell(&PARAMS, remaining_coin_supply, coin).unwrap() >= amount);
+        }
+
+        #[test]
+        fn prop_sell_rejects_more_than_the_reserves(
+            remaining_coin_supply in REVERSE_COINS..=MAX_COIN_SUPPLY,
+            extra in 1..=LAMPORTS_PER_SOL,
+        ) {
+            let amount = sol_reserves(remaining_coin_supply) + extra;
+            prop_assert!(sell(&PARAMS, remaining_coin_supply, amount).is_err());
+        }
+    }
 }
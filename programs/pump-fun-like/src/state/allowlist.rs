@@ -0,0 +1,41 @@
+use crate::state::deserialize_trailing_option;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use std::io::{self, Read};
+
+/// A merkle proof that a wallet is on the allowlist of a coin.
+///
+/// The leaf is `sha256(wallet || allocation)`, and each pair of nodes is hashed in sorted order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AllowlistProof {
+    /// The maximum amount of coin the wallet can buy during the allowlist window, 0 means no limit.
+    pub allocation: u64,
+    /// The sibling nodes from the leaf up to the root.
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl AllowlistProof {
+    pub fn leaf(wallet: &Pubkey, allocation: u64) -> [u8; 32] {
+        hashv(&[wallet.as_ref(), &allocation.to_le_bytes()]).to_bytes()
+    }
+
+    /// Returns whether the proof links the leaf of `wallet` to `root`.
+    pub fn verify(&self, root: &[u8; 32], wallet: &Pubkey) -> bool {
+        let node = self
+            .proof
+            .iter()
+            .fold(Self::leaf(wallet, self.allocation), |node, sibling| {
+                if node <= *sibling {
+                    hashv(&[&node, sibling]).to_bytes()
+                } else {
+                    hashv(&[sibling, &node]).to_bytes()
+                }
+            });
+        node == *root
+    }
+
+    /// Deserializes an optional proof placed at the end of the buy args, omitted by older clients.
+    pub fn deserialize_trailing<R: Read>(reader: &mut R) -> io::Result<Option<AllowlistProof>> {
+        deserialize_trailing_option(reader)
+    }
+}
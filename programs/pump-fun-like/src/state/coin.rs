@@ -1,14 +1,77 @@
 use crate::constants::{
-    COIN_SEED, FEE_RATE_BASIS_POINT, MAX_COIN_SUPPLY, REVERSE_COINS, SOL_VAULT_SEED,
-    SYMBOL_MAX_LEN, SYMBOL_MIN_LEN,
+    COIN_SEED, FEE_RATE_BASIS_POINT, SOL_VAULT_SEED, SYMBOL_MAX_LEN, SYMBOL_MIN_LEN,
 };
 use crate::errors::Error;
-use crate::math::{coin_math, fee_math, sol_math};
+use crate::math::curve::{BondingCurve, CurveKind, CurveParams};
+use crate::math::fee_math;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
+/// The lifecycle status of a coin.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoinStatus {
+    /// The bonding curve is open for trading.
+    #[default]
+    Trading,
+    /// All sellable coins are sold, waiting for migration.
+    Complete,
+    /// The sol and the reserved coins have been migrated.
+    Migrated,
+    /// Trading is halted by the authority.
+    Halted,
+}
+
+impl CoinStatus {
+    pub fn can_transition_to(self, to: CoinStatus) -> bool {
+        matches!(
+            (self, to),
+            (CoinStatus::Trading, CoinStatus::Complete)
+                | (CoinStatus::Trading, CoinStatus::Halted)
+                | (CoinStatus::Halted, CoinStatus::Trading)
+                | (CoinStatus::Complete, CoinStatus::Migrated)
+        )
+    }
+}
+
+/// The amounts moved by a trade on the bonding curve.
+pub struct Trade {
+    /// Amount of coin bought or sold.
+    pub coin_amount: u64,
+    /// Amount of sol paid or received, not including the fee.
+    pub sol_amount: u64,
+    /// Amount of fee paid.
+    pub fee: u64,
+}
+
+/// The outcome of a trade on the bonding curve, calculated without moving funds.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Quote {
+    /// Amount of coin bought or sold.
+    pub coin_amount: u64,
+    /// Amount of sol paid or received, not including the fee.
+    pub sol_amount: u64,
+    /// Amount of fee paid.
+    pub fee: u64,
+    /// Remaining coin supply on the bounding curve after the trade.
+    pub remaining_coin_supply: u64,
+    /// Accumulated sol on the bounding curve after the trade.
+    pub accumulate_sol: u64,
+    /// Whether the trade sells out the bounding curve and completes the coin.
+    pub complete: bool,
+}
+
+impl Quote {
+    pub fn trade(&self) -> Trade {
+        Trade {
+            coin_amount: self.coin_amount,
+            sol_amount: self.sol_amount,
+            fee: self.fee,
+        }
+    }
+}
+
 #[account]
 pub struct Coin {
     pub config: Pubkey,     // 32
@@ -22,10 +85,21 @@ pub struct Coin {
     pub symbol: String,     // 4 + 10
     pub coin_bump: [u8; 1], // 1
     pub sol_vault_bump: [u8; 1], // 1
+    pub status: CoinStatus, // 1
+    /// The slot in which the coin was created.
+    pub created_slot: u64, // 8
+    /// The merkle root of the wallets allowed to buy during the allowlist window.
+    pub allowlist_root: [u8; 32], // 32
+    /// The slot from which the public can trade, 0 if there is no allowlist window.
+    pub allowlist_end_slot: u64, // 8
+    /// The family of the bonding curve.
+    pub curve_kind: CurveKind, // 1
+    /// The parameters of the bonding curve, copied from the config at create and rescaled to the decimals of the coin.
+    pub curve_params: CurveParams, // CurveParams::LEN
 }
 
 impl Coin {
-    pub const LEN: usize = 8 + 32 * 4 + 8 * 2 + 4 + 10 + 1 * 2 + 24; // 24 is reversed
+    pub const LEN: usize = 8 + 32 * 5 + 8 * 4 + 4 + 10 + 2 + 1 + 1 + CurveParams::LEN + 14; // 14 is reversed
 
     pub fn coin_vault_seeds(&self) -> [&[u8]; 3] {
         [
@@ -43,6 +117,7 @@ impl Coin {
         ]
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize<'info>(
         &mut self,
         config: &Account<'info, Config>,
@@ -50,12 +125,30 @@ impl Coin {
         coin_vault: &Account<'info, TokenAccount>,
         symbol: String,
         coin_bump: u8,
-    ) {
+        created_slot: u64,
+        curve_kind: CurveKind,
+    ) -> Result<()> {
         self.config = config.key();
+        self.curve_params = config.curve_params.with_decimals(coin_mint.decimals)?;
+        self.curve_params.validate_kind(curve_kind)?;
         self.coin_mint = coin_mint.key();
         self.coin_vault = coin_vault.key();
         self.symbol = symbol;
         self.coin_bump = [coin_bump];
+        self.created_slot = created_slot;
+        self.curve_kind = curve_kind;
+        Ok(())
+    }
+
+    /// Opens an allowlist window of `allowlist_slots` slots from the creation slot.
+    pub fn initialize_allowlist(&mut self, root: [u8; 32], allowlist_slots: u64) -> Result<()> {
+        require!(allowlist_slots > 0, Error::InvalidAllowlist);
+        self.allowlist_root = root;
+        self.allowlist_end_slot = self
+            .created_slot
+            .checked_add(allowlist_slots)
+            .ok_or(Error::InvalidAllowlist)?;
+        Ok(())
     }
 
     pub fn create_sol_vault<'info>(
@@ -101,7 +194,7 @@ impl Coin {
         coin_vault: &Account<'info, TokenAccount>,
         token_program: &Program<'info, Token>,
     ) -> Result<()> {
-        self.remaining_coin_supply = MAX_COIN_SUPPLY;
+        self.remaining_coin_supply = self.curve_params.max_coin_supply;
         token::mint_to(
             CpiContext::new(
                 token_program.to_account_info(),
@@ -112,10 +205,11 @@ impl Coin {
                 },
             )
             .with_signer(&[&self.coin_vault_seeds()]),
-            MAX_COIN_SUPPLY,
+            self.curve_params.max_coin_supply,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn buy<'info>(
         &mut self,
         config: &Account<'info, Config>,
@@ -123,27 +217,20 @@ impl Coin {
         coin_vault: &Account<'info, TokenAccount>,
         sol_vault: &UncheckedAccount<'info>,
         fee_recipient: &UncheckedAccount<'info>,
-        coin_recipient: &Account<'info, TokenAccount>,
+        coin_recipient: &AccountInfo<'info>,
         payer: &Signer<'info>,
         token_program: &Program<'info, Token>,
         system_program: &Program<'info, System>,
         amount: u64,
         max_pay: u64,
-    ) -> Result<()> {
+        position: Option<&mut UserPosition>,
+    ) -> Result<Trade> {
+        let quote = self.quote_buy(config, amount)?;
         require!(
-            self.remaining_coin_supply > REVERSE_COINS,
-            Error::AlreadyLaunched
+            quote.sol_amount as u128 + quote.fee as u128 <= max_pay as u128,
+            Error::MaxPayExceeded
         );
-        let available_supply = self.available_supply();
-        require!(amount <= available_supply, Error::InsufficientSupply);
-
-        let pay_amount = coin_math::buy(self.remaining_coin_supply, amount);
-        let maker_fee = fee_math::buy(pay_amount, config.maker_fee_rate);
-        let total_pay: u128 = pay_amount as u128 + maker_fee as u128;
-        require!(total_pay <= max_pay as u128, Error::MaxPayExceeded);
-
-        self.remaining_coin_supply -= amount;
-        self.accumulate_sol += pay_amount;
+        self.apply_quote(&quote)?;
 
         self.buy_transfer(
             coin,
@@ -154,12 +241,19 @@ impl Coin {
             payer,
             token_program,
             system_program,
-            pay_amount,
-            maker_fee,
-            amount,
-        )
+            quote.sol_amount,
+            quote.fee,
+            quote.coin_amount,
+        )?;
+
+        let trade = quote.trade();
+        if let Some(position) = position {
+            position.record_buy(&trade)?;
+        }
+        Ok(trade)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn buy_exact_in<'info>(
         &mut self,
         config: &Account<'info, Config>,
@@ -167,23 +261,18 @@ impl Coin {
         coin_vault: &Account<'info, TokenAccount>,
         sol_vault: &UncheckedAccount<'info>,
         fee_recipient: &UncheckedAccount<'info>,
-        coin_recipient: &Account<'info, TokenAccount>,
+        coin_recipient: &AccountInfo<'info>,
         payer: &Signer<'info>,
         token_program: &Program<'info, Token>,
         system_program: &Program<'info, System>,
         pay_amount: u64,
+        fee_included: bool,
         min_receive: u64,
-    ) -> Result<()> {
-        require!(
-            self.remaining_coin_supply > REVERSE_COINS,
-            Error::AlreadyLaunched
-        );
-        let maker_fee = fee_math::buy(pay_amount, config.maker_fee_rate);
-        let actual_receive = sol_math::buy(self.remaining_coin_supply, pay_amount);
-        require!(actual_receive >= min_receive, Error::InsufficientReceive);
-
-        self.remaining_coin_supply -= actual_receive;
-        self.accumulate_sol += pay_amount;
+        position: Option<&mut UserPosition>,
+    ) -> Result<Trade> {
+        let quote = self.quote_buy_exact_in(config, pay_amount, fee_included)?;
+        require!(quote.coin_amount >= min_receive, Error::InsufficientReceive);
+        self.apply_quote(&quote)?;
 
         self.buy_transfer(
             coin,
@@ -194,40 +283,38 @@ impl Coin {
             payer,
             token_program,
             system_program,
-            pay_amount,
-            maker_fee,
-            actual_receive,
-        )
+            quote.sol_amount,
+            quote.fee,
+            quote.coin_amount,
+        )?;
+
+        let trade = quote.trade();
+        if let Some(position) = position {
+            position.record_buy(&trade)?;
+        }
+        Ok(trade)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn sell<'info>(
         &mut self,
         config: &Account<'info, Config>,
         coin_vault: &Account<'info, TokenAccount>,
         sol_vault: &UncheckedAccount<'info>,
         fee_recipient: &UncheckedAccount<'info>,
-        sol_recipient: &UncheckedAccount<'info>,
+        sol_recipient: &AccountInfo<'info>,
         coin_payer: &Account<'info, TokenAccount>,
         payer: &Signer<'info>,
         token_program: &Program<'info, Token>,
         system_program: &Program<'info, System>,
         amount: u64,
         min_receive: u64,
-    ) -> Result<()> {
-        require!(
-            self.remaining_coin_supply > REVERSE_COINS,
-            Error::AlreadyLaunched
-        );
-        let receive_with_fee = coin_math::sell(self.remaining_coin_supply, amount);
-        let taker_fee = fee_math::sell(receive_with_fee, config.taker_fee_rate);
-        let receive_without_fee = receive_with_fee - taker_fee;
-        require!(
-            receive_without_fee >= min_receive,
-            Error::InsufficientReceive
-        );
-
-        self.remaining_coin_supply += amount;
-        self.accumulate_sol -= receive_with_fee;
+        cooldown_tax_rate: u32,
+        position: Option<&mut UserPosition>,
+    ) -> Result<Trade> {
+        let quote = self.quote_sell(config, amount, cooldown_tax_rate)?;
+        require!(quote.sol_amount >= min_receive, Error::InsufficientReceive);
+        self.apply_quote(&quote)?;
 
         self.sell_transfer(
             coin_vault,
@@ -238,77 +325,433 @@ impl Coin {
             payer,
             token_program,
             system_program,
-            receive_without_fee,
-            taker_fee,
-            amount,
-        )
+            quote.sol_amount,
+            quote.fee,
+            quote.coin_amount,
+        )?;
+
+        let trade = quote.trade();
+        if let Some(position) = position {
+            position.record_sell(&trade)?;
+        }
+        Ok(trade)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn sell_exact_out<'info>(
         &mut self,
         config: &Account<'info, Config>,
         coin_vault: &Account<'info, TokenAccount>,
         sol_vault: &UncheckedAccount<'info>,
         fee_recipient: &UncheckedAccount<'info>,
-        sol_recipient: &UncheckedAccount<'info>,
+        sol_recipient: &AccountInfo<'info>,
         coin_payer: &Account<'info, TokenAccount>,
         payer: &Signer<'info>,
         token_program: &Program<'info, Token>,
         system_program: &Program<'info, System>,
         receive: u64,
         max_pay: u64,
-    ) -> Result<()> {
+        cooldown_tax_rate: u32,
+        position: Option<&mut UserPosition>,
+    ) -> Result<Trade> {
+        let quote = self.quote_sell_exact_out(config, receive, cooldown_tax_rate)?;
+        require!(quote.coin_amount <= max_pay, Error::MaxPayExceeded);
+        self.apply_quote(&quote)?;
+
+        self.sell_transfer(
+            coin_vault,
+            sol_vault,
+            fee_recipient,
+            sol_recipient,
+            coin_payer,
+            payer,
+            token_program,
+            system_program,
+            quote.sol_amount,
+            quote.fee,
+            quote.coin_amount,
+        )?;
+
+        let trade = quote.trade();
+        if let Some(position) = position {
+            position.record_sell(&trade)?;
+        }
+        Ok(trade)
+    }
+
+    /// Calculates a buy of `amount` coins on the bonding curve, without moving funds.
+    pub fn quote_buy(&self, config: &Config, amount: u64) -> Result<Quote> {
+        self.require_trading()?;
         require!(
-            self.remaining_coin_supply > REVERSE_COINS,
-            Error::AlreadyLaunched
+            amount <= self.available_supply()?,
+            Error::InsufficientSupply
         );
+
+        let pay_amount =
+            self.curve_kind
+                .buy(&self.curve_params, self.remaining_coin_supply, amount)?;
+        let maker_fee = fee_math::buy(pay_amount, self.buy_fee_rate(config)?)?;
+        self.buy_quote(config, amount, pay_amount, maker_fee)
+    }
+
+    /// Calculates a buy paying `pay_amount` sol on the bonding curve, without moving funds.
+    ///
+    /// The `pay_amount` does not include the fee, unless `fee_included` is set, in which case it is the total spent,
+    /// split into the sol paid to the curve and the maker fee so that their sum does not exceed it.
+    pub fn quote_buy_exact_in(
+        &self,
+        config: &Config,
+        pay_amount: u64,
+        fee_included: bool,
+    ) -> Result<Quote> {
+        self.require_trading()?;
+        let buy_fee_rate = self.buy_fee_rate(config)?;
+        let pay_amount = if fee_included {
+            fee_math::buy_pay_amount(pay_amount, buy_fee_rate)?
+        } else {
+            pay_amount
+        };
+        let coin_amount = self.curve_kind.buy_exact_in(
+            &self.curve_params,
+            self.remaining_coin_supply,
+            pay_amount,
+        )?;
+        // The receive is clamped to the available supply at the end of the bounding curve,
+        // only the sol needed to buy the available supply is charged.
+        let pay_amount = if coin_amount == self.available_supply()? {
+            pay_amount.min(self.curve_kind.buy(
+                &self.curve_params,
+                self.remaining_coin_supply,
+                coin_amount,
+            )?)
+        } else {
+            pay_amount
+        };
+        let maker_fee = fee_math::buy(pay_amount, buy_fee_rate)?;
+        self.buy_quote(config, coin_amount, pay_amount, maker_fee)
+    }
+
+    /// Calculates a sell of `amount` coins on the bonding curve, without moving funds.
+    ///
+    /// The `cooldown_tax_rate` is charged on top of the taker fee rate.
+    pub fn quote_sell(
+        &self,
+        config: &Config,
+        amount: u64,
+        cooldown_tax_rate: u32,
+    ) -> Result<Quote> {
+        self.require_trading()?;
+        let taker_fee_rate = Self::sell_fee_rate(config, cooldown_tax_rate)?;
+        let receive_with_fee =
+            self.curve_kind
+                .sell(&self.curve_params, self.remaining_coin_supply, amount)?;
+        let taker_fee = fee_math::sell(receive_with_fee, taker_fee_rate)?;
+        self.sell_quote(config, amount, receive_with_fee, taker_fee)
+    }
+
+    /// Calculates a sell receiving `receive` sol (after the fee) on the bonding curve, without moving funds.
+    ///
+    /// The `cooldown_tax_rate` is charged on top of the taker fee rate.
+    pub fn quote_sell_exact_out(
+        &self,
+        config: &Config,
+        receive: u64,
+        cooldown_tax_rate: u32,
+    ) -> Result<Quote> {
+        self.require_trading()?;
+        let taker_fee_rate = Self::sell_fee_rate(config, cooldown_tax_rate)?;
         require!(receive > 0, Error::InvalidReceive);
+        require!(
+            taker_fee_rate < FEE_RATE_BASIS_POINT,
+            Error::InsufficientReceive
+        );
         // total_receive = receive / (1 - taker_fee_rate)
         let total_receive = receive as u128 * FEE_RATE_BASIS_POINT as u128
-            / (FEE_RATE_BASIS_POINT as u128 - config.taker_fee_rate as u128);
-        let taker_fee = total_receive - receive as u128;
+            / (FEE_RATE_BASIS_POINT as u128 - taker_fee_rate as u128);
         require!(
             total_receive <= self.accumulate_sol as u128,
             Error::InsufficientReceive
         );
+        let total_receive = total_receive as u64;
+        let taker_fee = total_receive - receive;
+
+        let coin_amount = self.curve_kind.sell_exact_out(
+            &self.curve_params,
+            self.remaining_coin_supply,
+            total_receive,
+        )?;
+        self.sell_quote(config, coin_amount, total_receive, taker_fee)
+    }
+
+    /// Builds the quote of buying `coin_amount` coins for `pay_amount` sol plus the `maker_fee`.
+    fn buy_quote(
+        &self,
+        config: &Config,
+        coin_amount: u64,
+        pay_amount: u64,
+        maker_fee: u64,
+    ) -> Result<Quote> {
+        self.check_quote(
+            config,
+            Quote {
+                coin_amount,
+                sol_amount: pay_amount,
+                fee: maker_fee,
+                remaining_coin_supply: self
+                    .remaining_coin_supply
+                    .checked_sub(coin_amount)
+                    .ok_or(Error::MathUnderflow)?,
+                accumulate_sol: self
+                    .accumulate_sol
+                    .checked_add(pay_amount)
+                    .ok_or(Error::MathOverflow)?,
+                complete: false,
+            },
+        )
+    }
+
+    /// Builds the quote of selling `coin_amount` coins for `receive_with_fee` sol, of which the `taker_fee` is paid.
+    fn sell_quote(
+        &self,
+        config: &Config,
+        coin_amount: u64,
+        receive_with_fee: u64,
+        taker_fee: u64,
+    ) -> Result<Quote> {
+        self.check_quote(
+            config,
+            Quote {
+                coin_amount,
+                sol_amount: receive_with_fee
+                    .checked_sub(taker_fee)
+                    .ok_or(Error::MathUnderflow)?,
+                fee: taker_fee,
+                remaining_coin_supply: self
+                    .remaining_coin_supply
+                    .checked_add(coin_amount)
+                    .ok_or(Error::MathOverflow)?,
+                accumulate_sol: self
+                    .accumulate_sol
+                    .checked_sub(receive_with_fee)
+                    .ok_or(Error::MathUnderflow)?,
+                complete: false,
+            },
+        )
+    }
+
+    /// Checks the price impact of a quote against the config, and marks whether it completes the coin.
+    fn check_quote(&self, config: &Config, mut quote: Quote) -> Result<Quote> {
+        quote.complete = quote.remaining_coin_supply == self.curve_params.reverse_coins;
+        config.check_price_impact(
+            self.spot_price()?,
+            self.curve_kind
+                .spot_price(&self.curve_params, quote.remaining_coin_supply)?,
+        )?;
+        Ok(quote)
+    }
+
+    /// Moves the bonding curve to the state after a quoted trade.
+    fn apply_quote(&mut self, quote: &Quote) -> Result<()> {
+        self.remaining_coin_supply = quote.remaining_coin_supply;
+        self.accumulate_sol = quote.accumulate_sol;
+        self.complete_if_sold_out()?;
+        msg!(
+            "spot price: {}, market cap: {}, progress bps: {}",
+            self.spot_price()?,
+            self.market_cap()?,
+            self.progress_bps()?
+        );
+        Ok(())
+    }
 
-        let actual_pay = sol_math::sell(self.remaining_coin_supply, total_receive as u64)?;
-        require!(actual_pay <= max_pay, Error::MaxPayExceeded);
+    /// Moves the sol and the reserved coins of a completed bonding curve to the migration authority.
+    #[allow(clippy::too_many_arguments)]
+    pub fn migrate<'info>(
+        &mut self,
+        coin: &AccountInfo<'info>,
+        coin_vault: &Account<'info, TokenAccount>,
+        sol_vault: &UncheckedAccount<'info>,
+        coin_recipient: &Account<'info, TokenAccount>,
+        migration_authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<()> {
+        self.mark_migrated()?;
 
-        self.remaining_coin_supply += actual_pay;
-        self.accumulate_sol -= total_receive as u64;
+        // 1. transfer all sol to the migration authority
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: sol_vault.to_account_info(),
+                    to: migration_authority.to_account_info(),
+                },
+            )
+            .with_signer(&[&self.sol_vault_seeds()]),
+            sol_vault.lamports(),
+        )?;
 
-        self.sell_transfer(
-            coin_vault,
-            sol_vault,
-            fee_recipient,
-            sol_recipient,
-            coin_payer,
-            payer,
-            token_program,
-            system_program,
-            receive,
-            taker_fee as u64,
-            actual_pay,
+        // 2. transfer the reserved coins to the coin recipient
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: coin_vault.to_account_info(),
+                    to: coin_recipient.to_account_info(),
+                    authority: coin.to_account_info(),
+                },
+            )
+            .with_signer(&[&self.coin_vault_seeds()]),
+            self.remaining_coin_supply,
         )
     }
 
+    /// Marks the coin as migrated, the bonding curve must be completed and not migrated yet.
+    pub fn mark_migrated(&mut self) -> Result<()> {
+        self.status = self.current_status();
+        require!(self.status != CoinStatus::Migrated, Error::AlreadyMigrated);
+        require!(
+            self.status == CoinStatus::Complete,
+            Error::CurveNotCompleted
+        );
+        self.set_status(CoinStatus::Migrated)
+    }
+
+    /// Halts or resumes the trading on the bonding curve.
+    pub fn set_halted(&mut self, halted: bool) -> Result<()> {
+        self.status = self.current_status();
+        if halted {
+            self.set_status(CoinStatus::Halted)
+        } else {
+            self.set_status(CoinStatus::Trading)
+        }
+    }
+
+    /// Checks that the bonding curve is open for trading.
+    pub fn require_trading(&self) -> Result<()> {
+        match self.current_status() {
+            CoinStatus::Trading => {
+                require!(
+                    self.remaining_coin_supply > self.curve_params.reverse_coins,
+                    Error::AlreadyLaunched
+                );
+                Ok(())
+            }
+            CoinStatus::Complete => err!(Error::AlreadyLaunched),
+            CoinStatus::Migrated => err!(Error::AlreadyMigrated),
+            CoinStatus::Halted => err!(Error::CoinHalted),
+        }
+    }
+
+    /// Checks that `wallet` can buy in the current slot, with a proof while the allowlist window is open.
+    ///
+    /// Returns the allocation of the wallet during the allowlist window, 0 means no limit.
+    pub fn check_allowlist(&self, wallet: &Pubkey, proof: Option<&AllowlistProof>) -> Result<u64> {
+        if Clock::get()?.slot >= self.allowlist_end_slot {
+            return Ok(0);
+        }
+        let proof = proof.ok_or(Error::AllowlistProofRequired)?;
+        require!(
+            proof.verify(&self.allowlist_root, wallet),
+            Error::InvalidAllowlistProof
+        );
+        Ok(proof.allocation)
+    }
+
+    /// Returns whether the bonding curve is completed, so the coin can no longer be bought on it.
+    pub fn is_graduated(&self) -> bool {
+        matches!(
+            self.current_status(),
+            CoinStatus::Complete | CoinStatus::Migrated
+        )
+    }
+
+    /// Returns the status of the coin, reading a coin sold out before its status was tracked as completed.
+    pub fn current_status(&self) -> CoinStatus {
+        if self.status == CoinStatus::Trading
+            && self.remaining_coin_supply == self.curve_params.reverse_coins
+        {
+            CoinStatus::Complete
+        } else {
+            self.status
+        }
+    }
+
+    fn complete_if_sold_out(&mut self) -> Result<()> {
+        if self.remaining_coin_supply == self.curve_params.reverse_coins {
+            self.set_status(CoinStatus::Complete)?;
+        }
+        Ok(())
+    }
+
+    fn set_status(&mut self, status: CoinStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(status),
+            Error::InvalidStatusTransition
+        );
+        self.status = status;
+        Ok(())
+    }
+
+    /// Returns the fee rate of a buy in the current slot, the maker fee rate plus the launch tax rate.
+    pub fn buy_fee_rate(&self, config: &Config) -> Result<u32> {
+        let elapsed_slots = Clock::get()?.slot.saturating_sub(self.created_slot);
+        Ok(config
+            .maker_fee_rate
+            .checked_add(fee_math::launch_tax_rate(
+                config.launch_tax_rate,
+                config.launch_tax_slots,
+                elapsed_slots,
+            ))
+            .ok_or(Error::MathOverflow)?)
+    }
+
+    /// Returns the fee rate of a sell, the taker fee rate plus the `cooldown_tax_rate`.
+    pub fn sell_fee_rate(config: &Config, cooldown_tax_rate: u32) -> Result<u32> {
+        Ok(config
+            .taker_fee_rate
+            .checked_add(cooldown_tax_rate)
+            .ok_or(Error::MathOverflow)?)
+    }
+
     /// Returns the available supply of the coin.
-    pub fn available_supply(&self) -> u64 {
-        self.remaining_coin_supply - REVERSE_COINS
+    pub fn available_supply(&self) -> Result<u64> {
+        Ok(self
+            .remaining_coin_supply
+            .checked_sub(self.curve_params.reverse_coins)
+            .ok_or(Error::MathUnderflow)?)
+    }
+
+    /// Returns the spot price of the coin on its bonding curve, in lamports per smallest unit of coin scaled by
+    /// [crate::math::coin_math::SPOT_PRICE_SCALE].
+    pub fn spot_price(&self) -> Result<u128> {
+        self.curve_kind
+            .spot_price(&self.curve_params, self.remaining_coin_supply)
+    }
+
+    /// Returns the fully diluted market cap of the coin in lamports.
+    pub fn market_cap(&self) -> Result<u64> {
+        self.curve_kind
+            .market_cap(&self.curve_params, self.remaining_coin_supply)
+    }
+
+    /// Returns the progress of the bonding curve toward completion in basis points.
+    pub fn progress_bps(&self) -> Result<u16> {
+        self.curve_params.progress_bps(self.remaining_coin_supply)
     }
 
     pub fn check_symbol(symbol: &str) -> bool {
         symbol.len() >= SYMBOL_MIN_LEN && symbol.len() <= SYMBOL_MAX_LEN
     }
 
-    fn buy_transfer<'info>(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn buy_transfer<'info>(
         &self,
         coin: &AccountInfo<'info>,
         coin_vault: &Account<'info, TokenAccount>,
         sol_vault: &UncheckedAccount<'info>,
         fee_recipient: &UncheckedAccount<'info>,
-        coin_recipient: &Account<'info, TokenAccount>,
+        coin_recipient: &AccountInfo<'info>,
         payer: &Signer<'info>,
         token_program: &Program<'info, Token>,
         system_program: &Program<'info, System>,
@@ -355,12 +798,13 @@ impl Coin {
         )
     }
 
-    fn sell_transfer<'info>(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn sell_transfer<'info>(
         &self,
         coin_vault: &Account<'info, TokenAccount>,
         sol_vault: &UncheckedAccount<'info>,
         fee_recipient: &UncheckedAccount<'info>,
-        sol_recipient: &UncheckedAccount<'info>,
+        sol_recipient: &AccountInfo<'info>,
         coin_payer: &Account<'info, TokenAccount>,
         payer: &Signer<'info>,
         token_program: &Program<'info, Token>,
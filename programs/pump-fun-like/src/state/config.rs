@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::FEE_RATE_BASIS_POINT;
+use crate::constants::{BASIS_POINTS, FEE_RATE_BASIS_POINT};
 use crate::errors::Error;
+use crate::math::curve::CurveParams;
 
 #[account]
 pub struct Config {
@@ -11,20 +12,47 @@ pub struct Config {
     pub create_coin_fee: u64,
     pub taker_fee_rate: u32,
     pub maker_fee_rate: u32,
+    /// The maximum share of the supply the creator can buy in `create_and_buy`, in basis points.
+    /// 0 means no limit.
+    pub max_creator_buy_bps: u16,
+    /// Amount of sol escrowed with each order and paid to the keeper who fills it.
+    pub order_keeper_reward: u64,
+    /// The maximum share of the supply a wallet can buy on the bonding curve of a coin, in basis points.
+    /// 0 means no limit.
+    pub max_wallet_buy_bps: u16,
+    /// The extra fee rate of a buy in the creation slot of a coin, on top of `maker_fee_rate`.
+    pub launch_tax_rate: u32,
+    /// The number of slots over which the launch tax decays linearly to 0.
+    pub launch_tax_slots: u64,
+    /// The number of slots after the last buy of a wallet during which its sells are rejected or taxed.
+    /// 0 means no cooldown.
+    pub sell_cooldown_slots: u64,
+    /// The extra fee rate of a sell during the cooldown, on top of `taker_fee_rate`.
+    /// 0 means the sell is rejected instead.
+    pub cooldown_sell_tax_rate: u32,
+    /// The maximum change of the spot price a single trade on the bonding curve can cause, in basis points.
+    /// 0 means no limit.
+    pub max_price_impact_bps: u16,
+    /// The parameters of the bonding curve of the coins created with this config.
+    pub curve_params: CurveParams,
 }
 
 impl Config {
-    pub const LEN: usize = 8 + std::mem::size_of::<Config>();
+    pub const LEN: usize = 8 + std::mem::size_of::<Config>() + 128; // 128 is reserved
 
     pub fn initialize(
         &mut self,
         authority: Pubkey,
         fee_recipient: Pubkey,
         migration_authority: Pubkey,
-    ) {
+        curve_params: CurveParams,
+    ) -> Result<()> {
+        curve_params.validate()?;
         self.authority = authority;
         self.fee_recipient = fee_recipient;
         self.migration_authority = migration_authority;
+        self.curve_params = curve_params;
+        Ok(())
     }
 
     pub fn update_fee(
@@ -47,4 +75,108 @@ impl Config {
         self.maker_fee_rate = maker_fee_rate;
         Ok(())
     }
+
+    /// Sets the maximum share of the supply the creator can buy in `create_and_buy`, in basis points.
+    pub fn set_max_creator_buy_bps(&mut self, max_creator_buy_bps: u16) -> Result<()> {
+        require!(
+            max_creator_buy_bps <= BASIS_POINTS,
+            Error::InvalidCreatorBuyLimit
+        );
+        self.max_creator_buy_bps = max_creator_buy_bps;
+        Ok(())
+    }
+
+    /// Sets the amount of sol escrowed with each order and paid to the keeper who fills it.
+    pub fn set_order_keeper_reward(&mut self, order_keeper_reward: u64) {
+        self.order_keeper_reward = order_keeper_reward;
+    }
+
+    /// Sets the maximum share of the supply a wallet can buy on the bonding curve of a coin, in basis points.
+    pub fn set_max_wallet_buy_bps(&mut self, max_wallet_buy_bps: u16) -> Result<()> {
+        require!(
+            max_wallet_buy_bps <= BASIS_POINTS,
+            Error::InvalidWalletBuyLimit
+        );
+        self.max_wallet_buy_bps = max_wallet_buy_bps;
+        Ok(())
+    }
+
+    /// Sets the launch tax rate and the number of slots over which it decays.
+    pub fn set_launch_tax(&mut self, launch_tax_rate: u32, launch_tax_slots: u64) -> Result<()> {
+        require!(
+            launch_tax_rate <= FEE_RATE_BASIS_POINT,
+            Error::InvalidLaunchTaxRate
+        );
+        self.launch_tax_rate = launch_tax_rate;
+        self.launch_tax_slots = launch_tax_slots;
+        Ok(())
+    }
+
+    /// Sets the sell cooldown after the last buy of a wallet and the tax rate of a sell during it.
+    pub fn set_sell_cooldown(
+        &mut self,
+        sell_cooldown_slots: u64,
+        cooldown_sell_tax_rate: u32,
+    ) -> Result<()> {
+        require!(
+            cooldown_sell_tax_rate <= FEE_RATE_BASIS_POINT,
+            Error::InvalidCooldownSellTaxRate
+        );
+        self.sell_cooldown_slots = sell_cooldown_slots;
+        self.cooldown_sell_tax_rate = cooldown_sell_tax_rate;
+        Ok(())
+    }
+
+    /// Sets the maximum change of the spot price a single trade on the bonding curve can cause, in basis points.
+    pub fn set_max_price_impact_bps(&mut self, max_price_impact_bps: u16) {
+        self.max_price_impact_bps = max_price_impact_bps;
+    }
+
+    /// Sets the parameters of the bonding curve of the coins created from now on.
+    pub fn set_curve_params(&mut self, curve_params: CurveParams) -> Result<()> {
+        curve_params.validate()?;
+        self.curve_params = curve_params;
+        Ok(())
+    }
+
+    /// Checks the amount of coin the creator buys in `create_and_buy`, out of the `max_coin_supply` of the coin.
+    pub fn check_creator_buy(&self, amount: u64, max_coin_supply: u64) -> Result<()> {
+        if self.max_creator_buy_bps == 0 {
+            return Ok(());
+        }
+        let max_amount =
+            max_coin_supply as u128 * self.max_creator_buy_bps as u128 / BASIS_POINTS as u128;
+        require!(amount as u128 <= max_amount, Error::CreatorBuyLimitExceeded);
+        Ok(())
+    }
+
+    /// Checks the cumulative amount of coin a wallet bought on the bonding curve, out of the `max_coin_supply` of the coin.
+    pub fn check_wallet_buy(&self, amount: u64, max_coin_supply: u64) -> Result<()> {
+        if self.max_wallet_buy_bps == 0 {
+            return Ok(());
+        }
+        let max_amount =
+            max_coin_supply as u128 * self.max_wallet_buy_bps as u128 / BASIS_POINTS as u128;
+        require!(amount as u128 <= max_amount, Error::WalletBuyLimitExceeded);
+        Ok(())
+    }
+
+    /// Checks the change of the spot price caused by a trade on the bonding curve.
+    ///
+    /// A trade from a zero spot price has no relative impact, and is not checked.
+    pub fn check_price_impact(&self, price_before: u128, price_after: u128) -> Result<()> {
+        if self.max_price_impact_bps == 0 || price_before == 0 {
+            return Ok(());
+        }
+        let impact = price_before
+            .abs_diff(price_after)
+            .checked_mul(BASIS_POINTS as u128)
+            .ok_or(Error::MathOverflow)?
+            / price_before;
+        require!(
+            impact <= self.max_price_impact_bps as u128,
+            Error::PriceImpactExceeded
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,79 @@
+use crate::errors::Error;
+use crate::math::fee_math;
+use anchor_lang::prelude::*;
+
+/// A scheduled buy that spends the escrowed sol on the bonding curve of a coin, one slice per interval.
+///
+/// The sol is escrowed in the DCA account itself, and each slice is triggered by any cranker.
+#[account]
+pub struct Dca {
+    pub owner: Pubkey, // 32
+    pub coin: Pubkey,  // 32
+    /// Chosen by the owner to tell apart the DCA accounts on the same coin.
+    pub id: u64, // 8
+    /// Amount of sol to pay per slice, not including the fee.
+    pub pay_amount: u64, // 8
+    /// Minimum amount of coin to receive per full slice, scaled down for the last partial slice.
+    pub min_receive: u64, // 8
+    /// Seconds between two slices.
+    pub interval: i64, // 8
+    /// The unix timestamp from which the next slice can be executed.
+    pub next_slice_at: i64, // 8
+    /// Amount of sol left in the escrow, including the fees.
+    pub escrow: u64, // 8
+    pub bump: [u8; 1], // 1
+}
+
+impl Dca {
+    pub const LEN: usize = 8 + 32 * 2 + 8 * 6 + 1 + 32; // 32 is reserved
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        owner: Pubkey,
+        coin: Pubkey,
+        id: u64,
+        pay_amount: u64,
+        min_receive: u64,
+        interval: i64,
+        escrow: u64,
+        now: i64,
+        bump: u8,
+    ) {
+        self.owner = owner;
+        self.coin = coin;
+        self.id = id;
+        self.pay_amount = pay_amount;
+        self.min_receive = min_receive;
+        self.interval = interval;
+        self.next_slice_at = now;
+        self.escrow = escrow;
+        self.bump = [bump];
+    }
+
+    /// Returns the amount of sol to pay for the next slice, not including the fee.
+    ///
+    /// The last slice spends whatever is left in the escrow.
+    pub fn slice_pay_amount(&self, buy_fee_rate: u32) -> Result<u64> {
+        Ok(self
+            .pay_amount
+            .min(fee_math::buy_pay_amount(self.escrow, buy_fee_rate)?))
+    }
+
+    /// Returns the minimum amount of coin to receive for a slice paying `pay_amount`.
+    pub fn slice_min_receive(&self, pay_amount: u64) -> u64 {
+        (self.min_receive as u128 * pay_amount as u128 / self.pay_amount as u128) as u64
+    }
+
+    /// Records an executed slice which spent `spent` sol from the escrow.
+    pub fn record_slice(&mut self, spent: u64, now: i64) -> Result<()> {
+        self.escrow = self.escrow.checked_sub(spent).ok_or(Error::MathUnderflow)?;
+        self.next_slice_at = now.checked_add(self.interval).ok_or(Error::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Returns whether the escrow is too small to pay for another slice.
+    pub fn is_finished(&self, buy_fee_rate: u32) -> Result<bool> {
+        Ok(self.slice_pay_amount(buy_fee_rate)? == 0)
+    }
+}
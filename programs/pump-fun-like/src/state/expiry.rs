@@ -0,0 +1,65 @@
+use crate::errors::Error;
+use anchor_lang::prelude::*;
+use std::io::{self, Read};
+
+/// The moment after which a trade can no longer be executed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// The trade expires once the unix timestamp of the cluster is past this value.
+    UnixTimestamp { unix_timestamp: i64 },
+    /// The trade expires once the slot of the cluster is past this value.
+    Slot { slot: u64 },
+}
+
+impl Expiry {
+    pub fn is_expired(&self, clock: &Clock) -> bool {
+        match *self {
+            Expiry::UnixTimestamp { unix_timestamp } => clock.unix_timestamp > unix_timestamp,
+            Expiry::Slot { slot } => clock.slot > slot,
+        }
+    }
+
+    /// Fails with `TradeExpired` if the expiry is set and has passed.
+    pub fn require_not_expired(expiry: Option<Expiry>) -> Result<()> {
+        if let Some(expiry) = expiry {
+            require!(!expiry.is_expired(&Clock::get()?), Error::TradeExpired);
+        }
+        Ok(())
+    }
+
+    /// Deserializes an optional expiry placed at the end of the trade args.
+    ///
+    /// Older clients do not send the expiry at all, so running out of data is read as no expiry.
+    pub fn deserialize_trailing<R: Read>(reader: &mut R) -> io::Result<Option<Expiry>> {
+        deserialize_trailing_option(reader)
+    }
+}
+
+/// Deserializes a value placed at the end of the args, running out of data is read as the default.
+pub fn deserialize_trailing_or_default<T: AnchorDeserialize + Default, R: Read>(
+    reader: &mut R,
+) -> io::Result<T> {
+    let mut first = [0u8; 1];
+    if reader.read(&mut first)? == 0 {
+        return Ok(T::default());
+    }
+    T::deserialize_reader(&mut (&first[..]).chain(reader))
+}
+
+/// Deserializes an optional value placed at the end of the args, running out of data is read as `None`.
+pub fn deserialize_trailing_option<T: AnchorDeserialize, R: Read>(
+    reader: &mut R,
+) -> io::Result<Option<T>> {
+    let mut tag = [0u8; 1];
+    if reader.read(&mut tag)? == 0 {
+        return Ok(None);
+    }
+    match tag[0] {
+        0 => Ok(None),
+        1 => Ok(Some(T::deserialize_reader(reader)?)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid Option representation",
+        )),
+    }
+}
@@ -1,5 +1,19 @@
+pub mod allowlist;
 pub mod coin;
 pub mod config;
+pub mod dca;
+pub mod expiry;
+pub mod order;
+pub mod pool;
+pub mod user_position;
+pub mod user_state;
 
+pub use allowlist::*;
 pub use coin::*;
 pub use config::*;
+pub use dca::*;
+pub use expiry::*;
+pub use order::*;
+pub use pool::*;
+pub use user_position::*;
+pub use user_state::*;
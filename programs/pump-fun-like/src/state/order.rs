@@ -0,0 +1,166 @@
+use crate::constants::ORDER_SEED;
+use crate::errors::Error;
+use crate::math::curve::BondingCurve;
+use crate::math::fee_math;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    /// Buys coins with the escrowed sol.
+    Buy,
+    /// Sells the escrowed coins.
+    Sell,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderKind {
+    /// Triggered once the quote reaches the trigger amount, i.e. buy if the price falls to, or sell if the price rises to.
+    Limit,
+    /// Triggered once the quote falls to the trigger amount, i.e. buy if the price rises to, or sell if the price falls to.
+    Stop,
+}
+
+/// A resting order against the bonding curve of a coin, filled by any keeper once triggered.
+///
+/// The sol of a buy order is escrowed in the order account itself,
+/// the coins of a sell order are escrowed in the associated token account of the order.
+#[account]
+pub struct Order {
+    pub owner: Pubkey, // 32
+    pub coin: Pubkey,  // 32
+    /// Chosen by the owner to tell apart the orders on the same coin.
+    pub id: u64, // 8
+    pub side: OrderSide, // 1
+    pub kind: OrderKind, // 1
+    /// Buy: amount of sol to spend, including the fee. Sell: amount of coin to sell.
+    pub amount: u64, // 8
+    /// The quote at which the order is triggered.
+    /// Buy: amount of coin to receive. Sell: amount of sol to receive, not including the fee.
+    pub trigger_amount: u64, // 8
+    /// Minimum amount to receive when the order is filled, in the same unit as `trigger_amount`.
+    pub min_receive: u64, // 8
+    /// Amount of sol paid to the keeper who fills the order.
+    pub keeper_reward: u64, // 8
+    pub bump: [u8; 1], // 1
+}
+
+impl Order {
+    pub const LEN: usize = 8 + 32 * 2 + 8 * 5 + 3 + 32; // 32 is reserved
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        owner: Pubkey,
+        coin: Pubkey,
+        id: u64,
+        side: OrderSide,
+        kind: OrderKind,
+        amount: u64,
+        trigger_amount: u64,
+        min_receive: u64,
+        keeper_reward: u64,
+        bump: u8,
+    ) {
+        self.owner = owner;
+        self.coin = coin;
+        self.id = id;
+        self.side = side;
+        self.kind = kind;
+        self.amount = amount;
+        self.trigger_amount = trigger_amount;
+        self.min_receive = min_receive;
+        self.keeper_reward = keeper_reward;
+        self.bump = [bump];
+    }
+
+    /// Returns what filling the order on the current curve would receive, in the unit of `trigger_amount`.
+    pub fn quote(&self, config: &Config, coin: &Coin) -> Result<u64> {
+        Ok(match self.side {
+            OrderSide::Buy => coin.curve_kind.buy_exact_in(
+                &coin.curve_params,
+                coin.remaining_coin_supply,
+                self.pay_amount(coin.buy_fee_rate(config)?)?,
+            )?,
+            OrderSide::Sell => {
+                let receive_with_fee = coin.curve_kind.sell(
+                    &coin.curve_params,
+                    coin.remaining_coin_supply,
+                    self.amount,
+                )?;
+                receive_with_fee
+                    .checked_sub(fee_math::sell(receive_with_fee, config.taker_fee_rate)?)
+                    .ok_or(Error::MathUnderflow)?
+            }
+        })
+    }
+
+    /// Returns the amount of sol a buy order pays to the curve, so that the fee fits in the escrow.
+    pub fn pay_amount(&self, buy_fee_rate: u32) -> Result<u64> {
+        Ok(fee_math::buy_pay_amount(self.amount, buy_fee_rate)?)
+    }
+
+    pub fn is_triggered(&self, quote: u64) -> bool {
+        match self.kind {
+            OrderKind::Limit => quote >= self.trigger_amount,
+            OrderKind::Stop => quote <= self.trigger_amount,
+        }
+    }
+
+    /// Transfers the escrowed coins out of the coin vault of the order.
+    pub fn release_coins<'info>(
+        &self,
+        order: &AccountInfo<'info>,
+        order_coin_vault: &Account<'info, TokenAccount>,
+        to: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+        amount: u64,
+    ) -> Result<()> {
+        let id = self.id.to_le_bytes();
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: order_coin_vault.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: order.to_account_info(),
+                },
+            )
+            .with_signer(&[&self.seeds(&id)]),
+            amount,
+        )
+    }
+
+    /// Closes the coin vault of the order, the rent goes to `destination`.
+    pub fn close_coin_vault<'info>(
+        &self,
+        order: &AccountInfo<'info>,
+        order_coin_vault: &Account<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        let id = self.id.to_le_bytes();
+        token::close_account(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token::CloseAccount {
+                    account: order_coin_vault.to_account_info(),
+                    destination: destination.to_account_info(),
+                    authority: order.to_account_info(),
+                },
+            )
+            .with_signer(&[&self.seeds(&id)]),
+        )
+    }
+
+    fn seeds<'a>(&'a self, id: &'a [u8; 8]) -> [&'a [u8]; 5] {
+        [
+            ORDER_SEED.as_bytes(),
+            self.coin.as_ref(),
+            self.owner.as_ref(),
+            id,
+            self.bump.as_ref(),
+        ]
+    }
+}
@@ -0,0 +1,132 @@
+use crate::errors::Error;
+use crate::math::{fee_math, pool_math};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+/// A constant product (x * y = k) pool that takes over the trading of a coin after its bonding curve is completed.
+///
+/// The reserves are kept in the coin vault and the sol vault of the coin.
+#[account]
+pub struct Pool {
+    pub coin: Pubkey, // 32
+    /// Amount of sol in the pool, not including the rent of the sol vault.
+    pub sol_reserve: u64, // 8
+    /// Amount of coin in the pool.
+    pub coin_reserve: u64, // 8
+    pub bump: [u8; 1], // 1
+}
+
+impl Pool {
+    pub const LEN: usize = 8 + 32 + 8 * 2 + 1 + 32; // 32 is reserved
+
+    /// Seeds the pool with the sol and the reserved coins of the bonding curve.
+    pub fn initialize<'info>(
+        &mut self,
+        coin: &Account<'info, Coin>,
+        sol_vault: &UncheckedAccount<'info>,
+        rent: &Sysvar<'info, Rent>,
+        bump: u8,
+    ) -> Result<()> {
+        self.coin = coin.key();
+        self.sol_reserve = sol_vault
+            .lamports()
+            .checked_sub(rent.minimum_balance(0))
+            .ok_or(Error::InsufficientSolVaultBalance)?;
+        self.coin_reserve = coin.remaining_coin_supply;
+        self.bump = [bump];
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn buy<'info>(
+        &mut self,
+        config: &Account<'info, Config>,
+        coin: &Account<'info, Coin>,
+        coin_vault: &Account<'info, TokenAccount>,
+        sol_vault: &UncheckedAccount<'info>,
+        fee_recipient: &UncheckedAccount<'info>,
+        coin_recipient: &Account<'info, TokenAccount>,
+        payer: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+        pay_amount: u64,
+        min_receive: u64,
+    ) -> Result<()> {
+        let maker_fee = fee_math::buy(pay_amount, config.maker_fee_rate)?;
+        let actual_receive = pool_math::swap(self.sol_reserve, self.coin_reserve, pay_amount);
+        require!(actual_receive >= min_receive, Error::InsufficientReceive);
+
+        self.sol_reserve = self
+            .sol_reserve
+            .checked_add(pay_amount)
+            .ok_or(Error::MathOverflow)?;
+        self.coin_reserve = self
+            .coin_reserve
+            .checked_sub(actual_receive)
+            .ok_or(Error::MathUnderflow)?;
+
+        coin.buy_transfer(
+            &coin.to_account_info(),
+            coin_vault,
+            sol_vault,
+            fee_recipient,
+            &coin_recipient.to_account_info(),
+            payer,
+            token_program,
+            system_program,
+            pay_amount,
+            maker_fee,
+            actual_receive,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn sell<'info>(
+        &mut self,
+        config: &Account<'info, Config>,
+        coin: &Account<'info, Coin>,
+        coin_vault: &Account<'info, TokenAccount>,
+        sol_vault: &UncheckedAccount<'info>,
+        fee_recipient: &UncheckedAccount<'info>,
+        coin_payer: &Account<'info, TokenAccount>,
+        payer: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+        amount: u64,
+        min_receive: u64,
+    ) -> Result<()> {
+        let receive_with_fee = pool_math::swap(self.coin_reserve, self.sol_reserve, amount);
+        let taker_fee = fee_math::sell(receive_with_fee, config.taker_fee_rate)?;
+        let receive_without_fee = receive_with_fee
+            .checked_sub(taker_fee)
+            .ok_or(Error::MathUnderflow)?;
+        require!(
+            receive_without_fee >= min_receive,
+            Error::InsufficientReceive
+        );
+
+        self.sol_reserve = self
+            .sol_reserve
+            .checked_sub(receive_with_fee)
+            .ok_or(Error::MathUnderflow)?;
+        self.coin_reserve = self
+            .coin_reserve
+            .checked_add(amount)
+            .ok_or(Error::MathOverflow)?;
+
+        coin.sell_transfer(
+            coin_vault,
+            sol_vault,
+            fee_recipient,
+            payer,
+            coin_payer,
+            payer,
+            token_program,
+            system_program,
+            receive_without_fee,
+            taker_fee,
+            amount,
+        )
+    }
+}
@@ -0,0 +1,100 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Tracks the trading history of a wallet on the bonding curve of a coin, for dashboards.
+///
+/// The cost basis follows the average cost method, selling coins removes their share of the cost basis.
+#[account]
+pub struct UserPosition {
+    pub owner: Pubkey, // 32
+    pub coin: Pubkey,  // 32
+    /// Cumulative amount of coin bought.
+    pub coins_bought: u64, // 8
+    /// Cumulative amount of coin sold.
+    pub coins_sold: u64, // 8
+    /// Cumulative amount of sol paid, not including the fee.
+    pub sol_paid: u64, // 8
+    /// Cumulative amount of sol received, not including the fee.
+    pub sol_received: u64, // 8
+    /// Cumulative amount of fee paid on buys and sells.
+    pub fees_paid: u64, // 8
+    /// Number of trades recorded.
+    pub trade_count: u64, // 8
+    /// The sol spent on the coins still held, including the fees.
+    pub cost_basis: u64, // 8
+    /// The sol received minus the cost basis of the coins sold.
+    pub realized_pnl: i64, // 8
+    pub bump: [u8; 1], // 1
+}
+
+impl UserPosition {
+    pub const LEN: usize = 8 + 32 * 2 + 8 * 8 + 1 + 32; // 32 is reserved
+
+    pub fn initialize(&mut self, owner: Pubkey, coin: Pubkey, bump: u8) {
+        self.owner = owner;
+        self.coin = coin;
+        self.bump = [bump];
+    }
+
+    pub fn record_buy(&mut self, trade: &Trade) -> Result<()> {
+        let cost = trade
+            .sol_amount
+            .checked_add(trade.fee)
+            .ok_or(Error::MathOverflow)?;
+        self.coins_bought = self
+            .coins_bought
+            .checked_add(trade.coin_amount)
+            .ok_or(Error::MathOverflow)?;
+        self.sol_paid = self
+            .sol_paid
+            .checked_add(trade.sol_amount)
+            .ok_or(Error::MathOverflow)?;
+        self.fees_paid = self
+            .fees_paid
+            .checked_add(trade.fee)
+            .ok_or(Error::MathOverflow)?;
+        self.trade_count = self.trade_count.checked_add(1).ok_or(Error::MathOverflow)?;
+        self.cost_basis = self
+            .cost_basis
+            .checked_add(cost)
+            .ok_or(Error::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_sell(&mut self, trade: &Trade) -> Result<()> {
+        // Coins received from elsewhere have no cost basis, selling them takes what is left.
+        let held = self.coins_bought.saturating_sub(self.coins_sold);
+        let sold_cost = if trade.coin_amount >= held {
+            self.cost_basis
+        } else {
+            (self.cost_basis as u128 * trade.coin_amount as u128 / held as u128) as u64
+        };
+        let pnl = i64::try_from(trade.sol_amount)
+            .map_err(|_| Error::MathOverflow)?
+            .checked_sub(i64::try_from(sold_cost).map_err(|_| Error::MathOverflow)?)
+            .ok_or(Error::MathUnderflow)?;
+        self.coins_sold = self
+            .coins_sold
+            .checked_add(trade.coin_amount)
+            .ok_or(Error::MathOverflow)?;
+        self.sol_received = self
+            .sol_received
+            .checked_add(trade.sol_amount)
+            .ok_or(Error::MathOverflow)?;
+        self.fees_paid = self
+            .fees_paid
+            .checked_add(trade.fee)
+            .ok_or(Error::MathOverflow)?;
+        self.trade_count = self.trade_count.checked_add(1).ok_or(Error::MathOverflow)?;
+        self.cost_basis = self
+            .cost_basis
+            .checked_sub(sold_cost)
+            .ok_or(Error::MathUnderflow)?;
+        self.realized_pnl = self
+            .realized_pnl
+            .checked_add(pnl)
+            .ok_or(Error::MathOverflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Tracks the coins a wallet receives from buys on the bonding curve of a coin.
+#[account]
+pub struct UserState {
+    pub owner: Pubkey, // 32
+    pub coin: Pubkey,  // 32
+    /// Cumulative amount of coin bought on the bonding curve.
+    pub bought: u64, // 8
+    /// The slot of the last buy on the bonding curve.
+    pub last_buy_slot: u64, // 8
+    pub bump: [u8; 1], // 1
+}
+
+impl UserState {
+    pub const LEN: usize = 8 + 32 * 2 + 8 * 2 + 1 + 32; // 32 is reserved
+
+    /// Records the coins bought by the wallet, and checks them against the wallet buy limit.
+    pub fn record_buy(
+        &mut self,
+        config: &Config,
+        owner: Pubkey,
+        coin: &Account<Coin>,
+        bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.owner = owner;
+        self.coin = coin.key();
+        self.bump = [bump];
+        self.bought = self.bought.checked_add(amount).ok_or(Error::MathOverflow)?;
+        self.last_buy_slot = Clock::get()?.slot;
+        config.check_wallet_buy(self.bought, coin.curve_params.max_coin_supply)
+    }
+
+    /// Returns the extra fee rate of a sell by the wallet of `user_state`, while the cooldown after the last buy of
+    /// coins it received is active.
+    ///
+    /// Fails with `SellCooldownActive` if the sell is rejected instead. The user state may not exist yet.
+    pub fn cooldown_sell_tax_rate(config: &Config, user_state: &AccountInfo) -> Result<u32> {
+        if config.sell_cooldown_slots == 0 || user_state.data_is_empty() {
+            return Ok(0);
+        }
+        // The address is checked against the seeds, so only this program can have initialized it.
+        UserState::try_deserialize(&mut &user_state.try_borrow_data()?[..])?
+            .cooldown_tax_rate(config)
+    }
+
+    /// Returns the extra fee rate of a sell by the wallet, while the cooldown after its last buy is active.
+    ///
+    /// Fails with `SellCooldownActive` if the sell is rejected instead. A wallet without any buy has no cooldown.
+    pub fn cooldown_tax_rate(&self, config: &Config) -> Result<u32> {
+        if config.sell_cooldown_slots == 0 || self.bought == 0 {
+            return Ok(0);
+        }
+        let cooldown_end_slot = self
+            .last_buy_slot
+            .saturating_add(config.sell_cooldown_slots);
+        if Clock::get()?.slot >= cooldown_end_slot {
+            return Ok(0);
+        }
+        require!(config.cooldown_sell_tax_rate > 0, Error::SellCooldownActive);
+        Ok(config.cooldown_sell_tax_rate)
+    }
+
+    /// Checks the coins bought by the wallet against its allocation in the allowlist window, 0 means no limit.
+    pub fn check_allocation(&self, allocation: u64) -> Result<()> {
+        require!(
+            allocation == 0 || self.bought <= allocation,
+            Error::AllowlistAllocationExceeded
+        );
+        Ok(())
+    }
+}
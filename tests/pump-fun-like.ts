@@ -2,25 +2,61 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PumpFunLike } from "../target/types/pump_fun_like";
 import { expect } from "chai";
+import { createHash } from "crypto";
 import {
   createAssociatedTokenAccountInstruction,
   getAccount,
   getAssociatedTokenAddressSync,
+  getMint,
   getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import { fetchDigitalAsset } from "@metaplex-foundation/mpl-token-metadata";
 import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
 import { publicKey } from "@metaplex-foundation/umi";
-import { buy, sell } from "../clients/ts/src/math/coin_math";
-import { buy_fee, sell_fee } from "../clients/ts/src/math/fee_math";
-import { FEE_RATE_BASIS_POINT, MAX_COIN_SUPPLY, SELLABLE_COINS } from "../clients/ts/src/math/constants";
+import { buy, market_cap, progress_bps, sell, spot_price } from "../clients/ts/src/math/coin_math";
+import * as exponential_math from "../clients/ts/src/math/exponential_math";
+import * as linear_math from "../clients/ts/src/math/linear_math";
+import { buy_fee, buy_pay_amount, launch_tax_rate, sell_fee } from "../clients/ts/src/math/fee_math";
+import {
+  DECIMALS,
+  DEFAULT_CURVE_PARAMS,
+  EXPONENTIAL_DOUBLING_COINS,
+  EXPONENTIAL_SOL_SCALE,
+  FEE_RATE_BASIS_POINT,
+  LINEAR_OFFSET,
+  LINEAR_SCALE,
+  MAX_COIN_SUPPLY,
+  MAX_DECIMALS,
+  REVERSE_COINS,
+  SELLABLE_COINS,
+  VIRTUAL_COIN_RESERVES,
+  VIRTUAL_SOL_REVERSES,
+  with_decimals,
+} from "../clients/ts/src/math/constants";
 import { buy_exact_in, sell_exact_out } from "../clients/ts/src/math/sol_math";
+import { swap } from "../clients/ts/src/math/pool_math";
 
-const DECIMALS = 6;
 const COIN_SEED = "coin";
 const SOL_VAULT_SEED = "coin_sol_vault";
+const POOL_SEED = "pool";
+const ORDER_SEED = "order";
+const DCA_SEED = "dca";
+const USER_STATE_SEED = "user_state";
+const USER_POSITION_SEED = "user_position";
+const USER_STATE_LEN = 8 + 32 * 2 + 8 * 2 + 1 + 32;
 const METADATA_SEED = "metadata";
 const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+const CURVE_PARAMS = {
+  maxCoinSupply: new anchor.BN(MAX_COIN_SUPPLY.toString()),
+  reverseCoins: new anchor.BN(REVERSE_COINS.toString()),
+  virtualCoinReserves: new anchor.BN(VIRTUAL_COIN_RESERVES.toString()),
+  virtualSolReverses: new anchor.BN(VIRTUAL_SOL_REVERSES.toString()),
+  decimals: DECIMALS,
+  linearOffset: new anchor.BN(LINEAR_OFFSET.toString()),
+  linearScale: new anchor.BN(LINEAR_SCALE.toString()),
+  exponentialDoublingCoins: new anchor.BN(EXPONENTIAL_DOUBLING_COINS.toString()),
+  exponentialSolScale: new anchor.BN(EXPONENTIAL_SOL_SCALE.toString()),
+};
 
 describe("soc-pump-fun", () => {
   // Configure the client to use the local cluster.
@@ -44,6 +80,7 @@ describe("soc-pump-fun", () => {
             createCoinFee: new anchor.BN(1e9),
             makerFeeRate: Number(FEE_RATE_BASIS_POINT + 1n),
             takerFeeRate: Number(FEE_RATE_BASIS_POINT),
+            curveParams: CURVE_PARAMS,
           })
           .accounts({
             config: cfgAcctKeypair.publicKey,
@@ -72,6 +109,7 @@ describe("soc-pump-fun", () => {
             createCoinFee: new anchor.BN(1e9),
             makerFeeRate: Number(FEE_RATE_BASIS_POINT),
             takerFeeRate: Number(FEE_RATE_BASIS_POINT + 1n),
+            curveParams: CURVE_PARAMS,
           })
           .accounts({
             config: cfgAcctKeypair.publicKey,
@@ -99,6 +137,7 @@ describe("soc-pump-fun", () => {
           createCoinFee: new anchor.BN(1e9),
           makerFeeRate: Number(FEE_RATE_BASIS_POINT),
           takerFeeRate: Number(FEE_RATE_BASIS_POINT >> 1n),
+          curveParams: CURVE_PARAMS,
         })
         .accounts({
           config: cfgAcctKeypair.publicKey,
@@ -112,6 +151,41 @@ describe("soc-pump-fun", () => {
       expect(cfg.migrationAuthority.toBase58()).to.be.eq(migrationKeypair.publicKey.toBase58());
       expect(cfg.makerFeeRate).to.be.eq(Number(FEE_RATE_BASIS_POINT));
       expect(cfg.takerFeeRate).to.be.eq(Number(FEE_RATE_BASIS_POINT >> 1n));
+      expect(cfg.curveParams.maxCoinSupply.toString()).to.be.eq(MAX_COIN_SUPPLY.toString());
+      expect(cfg.curveParams.reverseCoins.toString()).to.be.eq(REVERSE_COINS.toString());
+      expect(cfg.curveParams.virtualCoinReserves.toString()).to.be.eq(VIRTUAL_COIN_RESERVES.toString());
+      expect(cfg.curveParams.virtualSolReverses.toString()).to.be.eq(VIRTUAL_SOL_REVERSES.toString());
+    });
+
+    it("should fail if the curve params are invalid", async () => {
+      for (const curveParams of [
+        { ...CURVE_PARAMS, reverseCoins: CURVE_PARAMS.maxCoinSupply },
+        { ...CURVE_PARAMS, virtualSolReverses: new anchor.BN(0) },
+      ]) {
+        const cfgAcctKeypair = anchor.web3.Keypair.generate();
+        try {
+          await program.methods
+            .initializeConfig({
+              authority: wallet.publicKey,
+              feeRecipient: wallet.publicKey,
+              migrationAuthority: wallet.publicKey,
+              createCoinFee: new anchor.BN(1e9),
+              makerFeeRate: Number(FEE_RATE_BASIS_POINT),
+              takerFeeRate: Number(FEE_RATE_BASIS_POINT),
+              curveParams: curveParams,
+            })
+            .accounts({
+              config: cfgAcctKeypair.publicKey,
+            })
+            .signers([cfgAcctKeypair])
+            .rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6044);
+        }
+      }
     });
   });
 
@@ -193,6 +267,198 @@ describe("soc-pump-fun", () => {
     });
   });
 
+  describe("#update_config", () => {
+    it("should fail if not the authority", async () => {
+      const { cfgAcctKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .updateConfig(updateConfigArgs({ maxCreatorBuyBps: 1000 }))
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: wallet.publicKey })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6002);
+      }
+    });
+
+    it("should fail if max_creator_buy_bps is too large", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .updateConfig(updateConfigArgs({ maxCreatorBuyBps: 10001 }))
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6022);
+      }
+    });
+
+    it("should fail if max_wallet_buy_bps is too large", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .updateConfig(updateConfigArgs({ maxWalletBuyBps: 10001 }))
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6033);
+      }
+    });
+
+    it("should fail if launch_tax_rate is too large", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .updateConfig(updateConfigArgs({ launchTaxRate: Number(FEE_RATE_BASIS_POINT) + 1 }))
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6035);
+      }
+    });
+
+    it("should fail if cooldown_sell_tax_rate is too large", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .updateConfig(updateConfigArgs({ cooldownSellTaxRate: Number(FEE_RATE_BASIS_POINT) + 1 }))
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6041);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(
+          updateConfigArgs({
+            maxCreatorBuyBps: 1000,
+            orderKeeperReward: new anchor.BN(1e6),
+            maxWalletBuyBps: 100,
+            maxPriceImpactBps: 500,
+          })
+        )
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const cfg = await program.account.config.fetch(cfgAcctKeypair.publicKey);
+      expect(cfg.maxCreatorBuyBps).to.be.eq(1000);
+      expect(cfg.orderKeeperReward.toNumber()).to.be.eq(1e6);
+      expect(cfg.maxWalletBuyBps).to.be.eq(100);
+      expect(cfg.maxPriceImpactBps).to.be.eq(500);
+    });
+
+    it("should keep the settings that are not set", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxCreatorBuyBps: 1000, maxWalletBuyBps: 100 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxWalletBuyBps: 200 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const cfg = await program.account.config.fetch(cfgAcctKeypair.publicKey);
+      expect(cfg.maxCreatorBuyBps).to.be.eq(1000);
+      expect(cfg.maxWalletBuyBps).to.be.eq(200);
+    });
+
+    it("should set the curve params", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      const curveParams = { ...CURVE_PARAMS, linearScale: CURVE_PARAMS.linearScale.muln(2) };
+      await program.methods
+        .updateConfig(updateConfigArgs({ curveParams }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const cfg = await program.account.config.fetch(cfgAcctKeypair.publicKey);
+      expect(cfg.curveParams.linearScale.toString()).to.be.eq(curveParams.linearScale.toString());
+    });
+
+    it("should fail if the curve params are invalid", async () => {
+      const { cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .updateConfig(
+            updateConfigArgs({ curveParams: { ...CURVE_PARAMS, exponentialDoublingCoins: new anchor.BN(0) } })
+          )
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6044);
+      }
+    });
+  });
+
+  describe("#realloc_config", () => {
+    it("should fail if not the authority", async () => {
+      const { cfgAcctKeypair } = await initializeConfig();
+      try {
+        await program.methods
+          .reallocConfig({ curveParams: null })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            authority: wallet.publicKey,
+            payer: wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6002);
+      }
+    });
+
+    it("should set the curve params", async () => {
+      const { cfgAcctKeypair, authorityKeypair, cfg } = await initializeConfig();
+      const curveParams = {
+        ...cfg.curveParams,
+        virtualSolReverses: cfg.curveParams.virtualSolReverses.muln(2),
+      };
+      await program.methods
+        .reallocConfig({ curveParams })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          authority: authorityKeypair.publicKey,
+          payer: wallet.publicKey,
+        })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const cfgAfter = await program.account.config.fetch(cfgAcctKeypair.publicKey);
+      expect(cfgAfter.curveParams.virtualSolReverses.toString()).to.be.eq(
+        curveParams.virtualSolReverses.toString()
+      );
+      expect(cfgAfter.authority.toBase58()).to.be.eq(authorityKeypair.publicKey.toBase58());
+    });
+  });
+
   describe("#create", () => {
     it("should failed if fee recipient mismatch", async () => {
       const { cfgAcctKeypair } = await initializeConfig();
@@ -206,6 +472,10 @@ describe("soc-pump-fun", () => {
         name: "Coin name",
         symbol: "CS",
         uri: "https://example.org",
+        allowlistRoot: null,
+        allowlistSlots: new anchor.BN(0),
+        curveKind: { constantProduct: {} },
+        decimals: DECIMALS,
       };
       const [coinPda, coinBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
@@ -250,6 +520,10 @@ describe("soc-pump-fun", () => {
         name: "Coin name",
         symbol: "CS",
         uri: "https://example.org",
+        allowlistRoot: null,
+        allowlistSlots: new anchor.BN(0),
+        curveKind: { constantProduct: {} },
+        decimals: DECIMALS,
       };
       const [coinPda, coinBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
@@ -294,6 +568,10 @@ describe("soc-pump-fun", () => {
         name: "Coin name",
         symbol: "CS",
         uri: "https://example.org",
+        allowlistRoot: null,
+        allowlistSlots: new anchor.BN(0),
+        curveKind: { constantProduct: {} },
+        decimals: DECIMALS,
       };
       const [coinPda, coinBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
@@ -355,6 +633,10 @@ describe("soc-pump-fun", () => {
         name: "Coin name",
         symbol: "CS",
         uri: "https://example.org",
+        allowlistRoot: null,
+        allowlistSlots: new anchor.BN(0),
+        curveKind: { constantProduct: {} },
+        decimals: DECIMALS,
       };
       const [coinPda, coinBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
@@ -407,6 +689,241 @@ describe("soc-pump-fun", () => {
       expect(coin.solVaultBump[0]).to.eq(solVaultBump);
       expect(coin.remainingCoinSupply.toNumber()).to.eq(Number(MAX_COIN_SUPPLY));
     });
+
+    it("should default the decimals if they are omitted by an older client", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+
+      const mintKeypair = anchor.web3.Keypair.generate();
+      const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(METADATA_SEED), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mintKeypair.publicKey.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      const [coinPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      const [solVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(SOL_VAULT_SEED), mintKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      const coinVaultAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, coinPda, true);
+      const createIX = await program.methods
+        .create({
+          name: "Coin name",
+          symbol: "CS",
+          uri: "https://example.org",
+          allowlistRoot: null,
+          allowlistSlots: new anchor.BN(0),
+          curveKind: { constantProduct: {} },
+          decimals: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinMint: mintKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          tokenMetadata: metadataPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+        })
+        .instruction();
+      // drop the tag of the `None` decimals, which is what an older client sends
+      createIX.data = createIX.data.subarray(0, createIX.data.length - 1);
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(createIX), wallet, mintKeypair);
+
+      const mint = await getMint(anchor.getProvider().connection, mintKeypair.publicKey);
+      expect(mint.decimals).to.eq(DECIMALS);
+      const coin = await program.account.coin.fetch(coinPda);
+      expect(coin.curveParams.maxCoinSupply.toString()).to.eq(MAX_COIN_SUPPLY.toString());
+    });
+
+    it("should create a token without an allowlist on the constant product curve for an original client", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, accounts } = createCoinAccounts(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const createIX = await program.methods
+        .create({
+          name: "Coin name",
+          symbol: "CS",
+          uri: "https://example.org",
+          allowlistRoot: null,
+          allowlistSlots: new anchor.BN(0),
+          curveKind: { linear: {} },
+          decimals: null,
+        })
+        .accountsPartial(accounts)
+        .instruction();
+      // drop the allowlist root tag, the allowlist slots, the curve kind and the decimals tag,
+      // which is what a client written before they were added sends
+      createIX.data = createIX.data.subarray(0, createIX.data.length - (1 + 8 + 1 + 1));
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(createIX), wallet, mintKeypair);
+
+      const coin = await program.account.coin.fetch(accounts.coin);
+      expect(coin.curveKind).to.be.deep.eq({ constantProduct: {} });
+      expect(coin.allowlistRoot).to.be.deep.eq(new Array(32).fill(0));
+      const mint = await getMint(anchor.getProvider().connection, mintKeypair.publicKey);
+      expect(mint.decimals).to.eq(DECIMALS);
+    });
+
+    it("should failed if the decimals are invalid", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      try {
+        await createCoin(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          undefined,
+          undefined,
+          undefined,
+          null,
+          undefined,
+          MAX_DECIMALS + 1
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6047);
+      }
+    });
+
+    it("should rescale the curve params to the decimals", async () => {
+      const sellableCoinsCost = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
+      for (const decimals of [0, DECIMALS, MAX_DECIMALS]) {
+        const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+        const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          undefined,
+          undefined,
+          undefined,
+          null,
+          undefined,
+          decimals
+        );
+        const mint = await getMint(anchor.getProvider().connection, mintKeypair.publicKey);
+        expect(mint.decimals).to.eq(decimals);
+
+        const params = with_decimals(DEFAULT_CURVE_PARAMS, decimals);
+        const coin = await program.account.coin.fetch(coinPda);
+        expect(coin.curveParams.maxCoinSupply.toString()).to.eq(params.max_coin_supply.toString());
+        expect(coin.curveParams.reverseCoins.toString()).to.eq(params.reverse_coins.toString());
+        expect(coin.curveParams.virtualCoinReserves.toString()).to.eq(params.virtual_coin_reserves.toString());
+        expect(coin.curveParams.decimals).to.eq(decimals);
+        expect(coin.remainingCoinSupply.toString()).to.eq(params.max_coin_supply.toString());
+        const coinVault = await getAccount(anchor.getProvider().connection, coinVaultAta);
+        expect(coinVault.amount).to.eq(params.max_coin_supply);
+
+        // a whole coin costs the same sol whatever the decimals
+        const buyAmount = params.max_coin_supply - params.reverse_coins;
+        const payWithoutFee = buy(params.max_coin_supply, buyAmount, params);
+        expect(payWithoutFee).to.eq(sellableCoinsCost);
+        const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+        const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+        await program.methods
+          .buy({
+            amount: new anchor.BN(buyAmount.toString()),
+            maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+            expiry: null,
+            allowlistProof: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+            recipient: wallet.publicKey,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+          })
+          .rpc();
+        const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+        expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
+      }
+    });
+
+    it("should failed if the allowlist window overflows", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      try {
+        await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey, "Coin name", "CS", "https://example.org", {
+          root: Buffer.alloc(32, 1),
+          slots: new anchor.BN("18446744073709551615"),
+        });
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.eq(6036);
+      }
+    });
+  });
+
+  describe("#realloc_coin", () => {
+    it("should failed if config mismatch", async () => {
+      const { feeRecipientKeypair, cfgAcctKeypair } = await initializeConfig();
+      const { cfgAcctKeypair: otherCfgAcctKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      try {
+        await program.methods
+          .reallocCoin()
+          .accountsPartial({
+            config: otherCfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinMint: mintKeypair.publicKey,
+            payer: wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.eq(6006);
+      }
+    });
+
+    it("should keep a coin created with the current layout", async () => {
+      const { feeRecipientKeypair, cfgAcctKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const coinInfoBefore = await anchor.getProvider().connection.getAccountInfo(coinPda);
+      await program.methods
+        .reallocCoin()
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinMint: mintKeypair.publicKey,
+          payer: wallet.publicKey,
+        })
+        .rpc();
+      const coinInfoAfter = await anchor.getProvider().connection.getAccountInfo(coinPda);
+      expect(coinInfoAfter.data.equals(coinInfoBefore.data)).to.be.true;
+      expect(coinInfoAfter.lamports).to.eq(coinInfoBefore.lamports);
+    });
+
+    it("should not reprice a coin after the curve params of its config changed", async () => {
+      const { feeRecipientKeypair, cfgAcctKeypair, authorityKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const curveParams = {
+        ...CURVE_PARAMS,
+        virtualSolReverses: CURVE_PARAMS.virtualSolReverses.muln(2),
+        linearScale: CURVE_PARAMS.linearScale.muln(2),
+      };
+      await program.methods
+        .updateConfig(updateConfigArgs({ curveParams }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+
+      await program.methods
+        .reallocCoin()
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinMint: mintKeypair.publicKey,
+          payer: wallet.publicKey,
+        })
+        .rpc();
+      const coin = await program.account.coin.fetch(coinPda);
+      expect(coin.curveParams.virtualSolReverses.toString()).to.eq(CURVE_PARAMS.virtualSolReverses.toString());
+      expect(coin.curveParams.linearScale.toString()).to.eq(CURVE_PARAMS.linearScale.toString());
+    });
   });
 
   describe("#buy", () => {
@@ -429,11 +946,14 @@ describe("soc-pump-fun", () => {
           .buy({
             amount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
             coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypairOther.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPda,
@@ -469,11 +989,14 @@ describe("soc-pump-fun", () => {
           .buy({
             amount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPdaOther,
             coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAtaOther,
             solVault: solVaultPdaOther,
@@ -511,11 +1034,14 @@ describe("soc-pump-fun", () => {
           .buy({
             amount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
             coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAtaOther,
             solVault: solVaultPda,
@@ -553,11 +1079,14 @@ describe("soc-pump-fun", () => {
           .buy({
             amount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
             coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPdaOther,
@@ -595,11 +1124,15 @@ describe("soc-pump-fun", () => {
           .buy({
             amount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
+            coinMint: mintKeypairOther.publicKey,
             coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPda,
@@ -639,11 +1172,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee - 1n).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -689,11 +1225,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -736,11 +1275,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN((SELLABLE_COINS + 1n).toString()),
           maxPay: new anchor.BN(1),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -786,11 +1328,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -845,11 +1390,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -866,7 +1414,8 @@ describe("soc-pump-fun", () => {
       expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
       expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
       const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
-      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee));
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee) + userStateRent);
 
       const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
       expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
@@ -878,199 +1427,61 @@ describe("soc-pump-fun", () => {
       expect(remainingCoinSupply.toNumber() + Number(buyAmount)).to.eq(Number(MAX_COIN_SUPPLY));
       expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee));
     });
-  });
-
-  describe("#buy_exact_in", () => {
-    it("should failed if fee recipient account mismatch", async () => {
-      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
-      const { feeRecipientKeypair: feeRecipientKeypairOther } = await initializeConfig();
-      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
-        cfgAcctKeypair.publicKey,
-        feeRecipientKeypair.publicKey
-      );
-
-      const coinRecipient = await getOrCreateAssociatedTokenAccount(
-        anchor.getProvider().connection,
-        wallet,
-        mintKeypair.publicKey,
-        wallet.publicKey
-      );
-      try {
-        await program.methods
-          .buyExactIn({
-            payAmount: new anchor.BN(1e9),
-            minReceive: new anchor.BN(1e9),
-          })
-          .accountsPartial({
-            config: cfgAcctKeypair.publicKey,
-            coin: coinPda,
-            coinRecipient: coinRecipient.address,
-            feeRecipient: feeRecipientKeypairOther.publicKey,
-            coinVault: coinVaultAta,
-            solVault: solVaultPda,
-          })
-          .rpc();
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.AnchorError).to.be.true;
-        const anchorError = e as anchor.AnchorError;
-        expect(anchorError.error.errorCode.number).to.be.eq(6003);
-      }
-    });
-
-    it("should failed if config account mismatch", async () => {
-      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
-      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
-        await initializeConfig();
-      const {
-        mintKeypair: mintKeypairOther,
-        coinPda: coinPdaOther,
-        coinVaultAta: coinVaultAtaOther,
-        solVaultPda: solVaultPdaOther,
-      } = await createCoin(cfgAcctKeypairOther.publicKey, feeRecipientKeypairOther.publicKey);
-
-      const coinRecipient = await getOrCreateAssociatedTokenAccount(
-        anchor.getProvider().connection,
-        wallet,
-        mintKeypairOther.publicKey,
-        wallet.publicKey
-      );
-      try {
-        await program.methods
-          .buyExactIn({
-            payAmount: new anchor.BN(1e9),
-            minReceive: new anchor.BN(1e9),
-          })
-          .accountsPartial({
-            config: cfgAcctKeypair.publicKey,
-            coin: coinPdaOther,
-            coinRecipient: coinRecipient.address,
-            feeRecipient: feeRecipientKeypair.publicKey,
-            coinVault: coinVaultAtaOther,
-            solVault: solVaultPdaOther,
-          })
-          .rpc();
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.AnchorError).to.be.true;
-        const anchorError = e as anchor.AnchorError;
-        expect(anchorError.error.errorCode.number).to.be.eq(6006);
-      }
-    });
-
-    it("should failed if coin vault account mismatch", async () => {
-      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
-      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
-        await initializeConfig();
-      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
-        cfgAcctKeypair.publicKey,
-        feeRecipientKeypair.publicKey
-      );
-      const { coinVaultAta: coinVaultAtaOther } = await createCoin(
-        cfgAcctKeypairOther.publicKey,
-        feeRecipientKeypairOther.publicKey
-      );
-
-      const coinRecipient = await getOrCreateAssociatedTokenAccount(
-        anchor.getProvider().connection,
-        wallet,
-        mintKeypair.publicKey,
-        wallet.publicKey
-      );
-      try {
-        await program.methods
-          .buyExactIn({
-            payAmount: new anchor.BN(1e9),
-            minReceive: new anchor.BN(1e9),
-          })
-          .accountsPartial({
-            config: cfgAcctKeypair.publicKey,
-            coin: coinPda,
-            coinRecipient: coinRecipient.address,
-            feeRecipient: feeRecipientKeypair.publicKey,
-            coinVault: coinVaultAtaOther,
-            solVault: solVaultPda,
-          })
-          .rpc();
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.AnchorError).to.be.true;
-        const anchorError = e as anchor.AnchorError;
-        expect(anchorError.error.errorCode.number).to.be.eq(6007);
-      }
-    });
 
-    it("should failed if sol vault account mismatch", async () => {
+    it("should failed if expired by unix timestamp", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
-      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
-        await initializeConfig();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
         feeRecipientKeypair.publicKey
       );
-      const { solVaultPda: solVaultPdaOther } = await createCoin(
-        cfgAcctKeypairOther.publicKey,
-        feeRecipientKeypairOther.publicKey
-      );
 
-      const coinRecipient = await getOrCreateAssociatedTokenAccount(
-        anchor.getProvider().connection,
-        wallet,
-        mintKeypair.publicKey,
-        wallet.publicKey
-      );
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
       try {
         await program.methods
-          .buyExactIn({
-            payAmount: new anchor.BN(1e9),
-            minReceive: new anchor.BN(1e9),
+          .buy({
+            amount: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: { unixTimestamp: { unixTimestamp: new anchor.BN(Math.floor(Date.now() / 1000) - 3600) } },
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
-            coinRecipient: coinRecipient.address,
+            coinRecipient: coinRecipientAta,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAta,
-            solVault: solVaultPdaOther,
+            solVault: solVaultPda,
           })
           .rpc();
         expect.fail("should have failed");
       } catch (e) {
         expect(e instanceof anchor.AnchorError).to.be.true;
         const anchorError = e as anchor.AnchorError;
-        expect(anchorError.error.errorCode.number).to.be.eq(6004);
+        expect(anchorError.error.errorCode.number).to.be.eq(6025);
       }
     });
 
-    it("should failed if coin mint account mismatch", async () => {
+    it("should failed if expired by slot", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
-      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
-        await initializeConfig();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
         feeRecipientKeypair.publicKey
       );
-      const { mintKeypair: mintKeypairOther } = await createCoin(
-        cfgAcctKeypairOther.publicKey,
-        feeRecipientKeypairOther.publicKey
-      );
 
-      const coinRecipient = await getOrCreateAssociatedTokenAccount(
-        anchor.getProvider().connection,
-        wallet,
-        mintKeypairOther.publicKey,
-        wallet.publicKey
-      );
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      const slot = await anchor.getProvider().connection.getSlot();
       try {
         await program.methods
-          .buyExactIn({
-            payAmount: new anchor.BN(1e9),
-            minReceive: new anchor.BN(1e9),
+          .buy({
+            amount: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: { slot: { slot: new anchor.BN(slot - 1) } },
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
-            coinRecipient: coinRecipient.address,
+            coinRecipient: coinRecipientAta,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPda,
@@ -1080,60 +1491,47 @@ describe("soc-pump-fun", () => {
       } catch (e) {
         expect(e instanceof anchor.AnchorError).to.be.true;
         const anchorError = e as anchor.AnchorError;
-        expect(anchorError.error.errorCode.number).to.be.eq(6008);
+        expect(anchorError.error.errorCode.number).to.be.eq(6025);
       }
     });
 
-    it("should failed if min receive not match", async () => {
+    it("should succeed if the expiry and the allowlist proof are omitted by an older client", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
         feeRecipientKeypair.publicKey
       );
 
-      const payer = anchor.web3.Keypair.generate();
-      const payerBalanceBefore = 1e9 * 100;
-      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
-      await confirmTransaction(tx);
-
-      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
-      const createAtaIX = createAssociatedTokenAccountInstruction(
-        wallet.publicKey,
-        coinRecipientAta,
-        payer.publicKey,
-        mintKeypair.publicKey
-      );
-      const payAmountWithoutFee = 10n * BigInt(1e9);
-      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payAmountWithoutFee);
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
       const buyIX = await program.methods
-        .buyExactIn({
-          payAmount: new anchor.BN(payAmountWithoutFee.toString()),
-          minReceive: new anchor.BN((buyAmount + 1n).toString()),
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: wallet.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
-          payer: payer.publicKey,
         })
         .instruction();
+      // drop the tags of the `None` expiry and allowlist proof, which is what an older client sends
+      buyIX.data = buyIX.data.subarray(0, buyIX.data.length - 2);
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(buyIX), wallet);
 
-      const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX);
-      try {
-        await sendAndConfirmTransaction(transaction, wallet, payer);
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
-        const sendTxError = e as anchor.web3.SendTransactionError;
-        expect(sendTxError.message.includes(BigInt(6011).toString(16))).to.be.true;
-      }
+      const coinRecipientAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientAfter.amount).to.eq(buyAmount);
     });
 
-    it("should failed if payer balance insufficient", async () => {
+    it("should create the recipient token account if it does not exist", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
@@ -1141,232 +1539,470 @@ describe("soc-pump-fun", () => {
       );
 
       const payer = anchor.web3.Keypair.generate();
-      const payerBalanceBefore = 1e9;
+      const payerBalanceBefore = 1e9 * 100;
       const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
       await confirmTransaction(tx);
 
-      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
-      const createAtaIX = createAssociatedTokenAccountInstruction(
-        wallet.publicKey,
-        coinRecipientAta,
-        payer.publicKey,
-        mintKeypair.publicKey
-      );
-      const payAmountWithoutFee = BigInt(1e9);
-      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payAmountWithoutFee);
+      const recipient = anchor.web3.Keypair.generate();
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, recipient.publicKey);
+      expect(await anchor.getProvider().connection.getAccountInfo(coinRecipientAta)).to.be.null;
+
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
       const buyIX = await program.methods
-        .buyExactIn({
-          payAmount: new anchor.BN(payAmountWithoutFee.toString()),
-          minReceive: new anchor.BN(buyAmount.toString()),
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: recipient.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
           payer: payer.publicKey,
         })
         .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(buyIX), wallet, payer);
 
-      const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX);
-      try {
-        await sendAndConfirmTransaction(transaction, wallet, payer);
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.web3.SendTransactionError);
-        const sendTxError = e as anchor.web3.SendTransactionError;
-        expect(sendTxError.logs.join(" ").includes("insufficient lamports")).to.be.true;
-      }
+      // the payer also pays the rent of the new token account and of its user state
+      const rent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(165);
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee) + rent + userStateRent);
+
+      const coinRecipientAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientAfter.owner.toBase58()).to.eq(recipient.publicKey.toBase58());
+      expect(coinRecipientAfter.mint.toBase58()).to.eq(mintKeypair.publicKey.toBase58());
+      expect(coinRecipientAfter.amount).to.eq(buyAmount);
     });
 
-    it("should not failed if pay too more (sellable coins insufficient)", async () => {
-      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+    it("should charge the launch tax after create", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      // 50% on top of the maker fee, decaying to 0 over 1000 slots
+      const launchTaxRate = FEE_RATE_BASIS_POINT / 2n;
+      const launchTaxSlots = 1000n;
+      await program.methods
+        .updateConfig(
+          updateConfigArgs({
+            launchTaxRate: Number(launchTaxRate),
+            launchTaxSlots: new anchor.BN(launchTaxSlots.toString()),
+          })
+        )
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
         feeRecipientKeypair.publicKey
       );
+      const coin = await program.account.coin.fetch(coinPda);
 
-      const payer = anchor.web3.Keypair.generate();
-      const payerBalanceBefore = 1e9 * 100;
-      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
-      await confirmTransaction(tx);
-
-      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
       const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
-
-      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
-      const createAtaIX = createAssociatedTokenAccountInstruction(
-        wallet.publicKey,
-        coinRecipientAta,
-        payer.publicKey,
-        mintKeypair.publicKey
-      );
-      const pay = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
-      const actualPay = pay + BigInt(1e9);
-      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, actualPay);
-      const fee = buy_fee(actualPay, BigInt(cfg.makerFeeRate));
-      const buyIX = await program.methods
-        .buyExactIn({
-          payAmount: new anchor.BN(actualPay.toString()),
-          minReceive: new anchor.BN(buyAmount.toString()),
+      const signature = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN(1e11),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
-          coinRecipient: coinRecipientAta,
+          coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+          recipient: wallet.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
-          payer: payer.publicKey,
         })
-        .instruction();
-
-      const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX);
-      await sendAndConfirmTransaction(transaction, wallet, payer);
+        .rpc({ commitment: "confirmed" });
+      const tx = await anchor.getProvider().connection.getTransaction(signature, { commitment: "confirmed" });
 
-      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const elapsedSlots = BigInt(tx.slot) - BigInt(coin.createdSlot.toString());
+      const taxRate = launch_tax_rate(launchTaxRate, launchTaxSlots, elapsedSlots);
+      expect(taxRate > 0n).to.be.true;
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate) + taxRate);
       const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
       expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
-      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(actualPay));
-      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
-      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(actualPay + fee));
+    });
 
-      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
-      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
+    it("should log the spot price, the market cap and the progress", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
 
-      const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
-      expect(coinVaultBalanceAfter.amount + buyAmount).to.eq(MAX_COIN_SUPPLY);
+      const buyAmount = SELLABLE_COINS / 4n;
+      const signature = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN(1e11),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+          recipient: wallet.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+        })
+        .rpc({ commitment: "confirmed" });
+      const tx = await anchor.getProvider().connection.getTransaction(signature, { commitment: "confirmed" });
 
-      const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
-      expect(remainingCoinSupply.toNumber() + Number(buyAmount)).to.eq(Number(MAX_COIN_SUPPLY));
-      expect(accumulateSol.toNumber()).to.eq(Number(actualPay));
+      const price = spot_price(MAX_COIN_SUPPLY - buyAmount);
+      expect(progress_bps(MAX_COIN_SUPPLY - buyAmount)).to.eq(2500n);
+      expect(tx.meta.logMessages).to.include(
+        `Program log: spot price: ${price}, market cap: ${market_cap(price)}, progress bps: 2500`
+      );
     });
 
-    it("should failed if migration limit exceeded", async () => {
-      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+    it("should price the coins with the curve params of the config", async () => {
+      const curveParams = { ...CURVE_PARAMS, virtualSolReverses: CURVE_PARAMS.virtualSolReverses.muln(2) };
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig(
+        undefined,
+        undefined,
+        undefined,
+        curveParams
+      );
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
         feeRecipientKeypair.publicKey
       );
+      const coin = await program.account.coin.fetch(coinPda);
+      expect(coin.curveParams.virtualSolReverses.toString()).to.eq(curveParams.virtualSolReverses.toString());
 
-      const payer = anchor.web3.Keypair.generate();
-      const payerBalanceBefore = 1e9 * 100;
-      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
-      await confirmTransaction(tx);
-
-      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
-      const createAtaIX = createAssociatedTokenAccountInstruction(
-        wallet.publicKey,
-        coinRecipientAta,
-        payer.publicKey,
-        mintKeypair.publicKey
-      );
-      const buyAmount = SELLABLE_COINS;
-      const pay = buy(MAX_COIN_SUPPLY, buyAmount);
-      const actualPay = pay + BigInt(1e9);
-      const buyIX = await program.methods
-        .buyExactIn({
-          payAmount: new anchor.BN(actualPay.toString()),
-          minReceive: new anchor.BN(buyAmount.toString()),
+      const params = { ...DEFAULT_CURVE_PARAMS, virtual_sol_reverses: VIRTUAL_SOL_REVERSES * 2n };
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount, params);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
-          coinRecipient: coinRecipientAta,
+          coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+          recipient: wallet.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
-          payer: payer.publicKey,
         })
-        .instruction();
+        .rpc();
+      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
+      expect(payWithoutFee > buy(MAX_COIN_SUPPLY, buyAmount)).to.be.true;
+    });
 
-      const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX);
-      await sendAndConfirmTransaction(transaction, wallet, payer);
+    it("should buy on the linear and the exponential curve", async () => {
+      const curves = [
+        { curveKind: { linear: {} }, math: linear_math },
+        { curveKind: { exponential: {} }, math: exponential_math },
+      ];
+      for (const { curveKind, math } of curves) {
+        const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+        const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          "Coin name",
+          "CS",
+          "https://example.org",
+          null,
+          curveKind
+        );
+        expect((await program.account.coin.fetch(coinPda)).curveKind).to.deep.eq(curveKind);
 
-      {
-        const transaction = new anchor.web3.Transaction();
-        transaction.add(buyIX);
-        try {
-          await sendAndConfirmTransaction(transaction, wallet, payer);
-          expect.fail("should have failed");
-        } catch (e) {
-          expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
-          const sendTxError = e as anchor.web3.SendTransactionError;
-          expect(sendTxError.message.includes(BigInt(6012).toString(16))).to.be.true;
-        }
+        const buyAmount = BigInt(1e8) * BigInt(1e6);
+        const payWithoutFee = math.buy(MAX_COIN_SUPPLY, buyAmount);
+        const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+        expect(payWithoutFee).to.not.eq(buy(MAX_COIN_SUPPLY, buyAmount));
+        const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+        await program.methods
+          .buy({
+            amount: new anchor.BN(buyAmount.toString()),
+            maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+            expiry: null,
+            allowlistProof: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+            recipient: wallet.publicKey,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+          })
+          .rpc();
+        const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+        expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
+
+        const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
+        expect(accumulateSol.toString()).to.eq(payWithoutFee.toString());
+        const receiveWithFee = math.sell(BigInt(remainingCoinSupply.toString()), buyAmount);
+        expect(receiveWithFee <= payWithoutFee).to.be.true;
       }
     });
 
-    it("should succeed", async () => {
-      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+    it("should failed to create a coin on a curve the config does not offer", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig(
+        undefined,
+        undefined,
+        undefined,
+        { ...CURVE_PARAMS, linearOffset: new anchor.BN(0), linearScale: new anchor.BN(0) }
+      );
+      try {
+        await createCoin(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          "Coin name",
+          "CS",
+          "https://example.org",
+          null,
+          { linear: {} }
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6044);
+      }
+    });
+
+    it("should failed if price impact exceeded", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxPriceImpactBps: 500 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
         feeRecipientKeypair.publicKey
       );
+      const accounts = {
+        config: cfgAcctKeypair.publicKey,
+        coin: coinPda,
+        coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+        recipient: wallet.publicKey,
+        feeRecipient: feeRecipientKeypair.publicKey,
+        coinVault: coinVaultAta,
+        solVault: solVaultPda,
+      };
 
-      const payer = anchor.web3.Keypair.generate();
-      const payerBalanceBefore = 1e9 * 100;
-      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
-      await confirmTransaction(tx);
+      // 2% of the supply moves the price by about 3.8%, 3% by about 5.8%
+      const priceBefore = spot_price(MAX_COIN_SUPPLY);
+      const smallAmount = (MAX_COIN_SUPPLY * 2n) / 100n;
+      expect(((spot_price(MAX_COIN_SUPPLY - smallAmount) - priceBefore) * 10000n) / priceBefore <= 500n).to.be.true;
+      const largeAmount = (MAX_COIN_SUPPLY * 3n) / 100n;
+      expect(((spot_price(MAX_COIN_SUPPLY - largeAmount) - priceBefore) * 10000n) / priceBefore > 500n).to.be.true;
 
-      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
-      const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      try {
+        await program.methods
+          .buy({
+            amount: new anchor.BN(largeAmount.toString()),
+            maxPay: new anchor.BN(1e11),
+            expiry: null,
+            allowlistProof: null,
+          })
+          .accountsPartial(accounts)
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6043);
+      }
 
-      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
-      const createAtaIX = createAssociatedTokenAccountInstruction(
-        wallet.publicKey,
-        coinRecipientAta,
-        payer.publicKey,
-        mintKeypair.publicKey
+      await program.methods
+        .buy({
+          amount: new anchor.BN(smallAmount.toString()),
+          maxPay: new anchor.BN(1e11),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial(accounts)
+        .rpc();
+      const { remainingCoinSupply } = await program.account.coin.fetch(coinPda);
+      expect(remainingCoinSupply.toString()).to.eq((MAX_COIN_SUPPLY - smallAmount).toString());
+    });
+
+    it("should failed if wallet buy limit exceeded", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxWalletBuyBps: 100 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
       );
-      const payWithoutFee = 10n * BigInt(1e9);
-      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
-      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
-      const buyIX = await program.methods
-        .buyExactIn({
-          payAmount: new anchor.BN(payWithoutFee.toString()),
-          minReceive: new anchor.BN(buyAmount.toString()),
+
+      // each buy is 0.6% of the supply, the limit is 1% per wallet
+      const buyAmount = (MAX_COIN_SUPPLY * 6n) / 1000n;
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      const accounts = {
+        config: cfgAcctKeypair.publicKey,
+        coin: coinPda,
+        coinRecipient: coinRecipientAta,
+        recipient: wallet.publicKey,
+        feeRecipient: feeRecipientKeypair.publicKey,
+        coinVault: coinVaultAta,
+        solVault: solVaultPda,
+      };
+      await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN(1e11),
+          expiry: null,
+          allowlistProof: null,
         })
-        .accountsPartial({
+        .accountsPartial(accounts)
+        .rpc();
+      const [userStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(USER_STATE_SEED), coinPda.toBuffer(), wallet.publicKey.toBuffer()],
+        program.programId
+      );
+      const userState = await program.account.userState.fetch(userStatePda);
+      expect(userState.bought.toString()).to.eq(buyAmount.toString());
+
+      try {
+        await program.methods
+          .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN(1e11),
+          expiry: null,
+          allowlistProof: null,
+        })
+          .accountsPartial(accounts)
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6034);
+      }
+    });
+
+    describe("during the allowlist window", () => {
+      const allocation = (MAX_COIN_SUPPLY * 6n) / 1000n;
+      const otherWallet = anchor.web3.Keypair.generate().publicKey;
+      // a tree of two leaves, the wallet with an allocation and another wallet without
+      const walletLeaf = allowlistLeaf(wallet.publicKey, allocation);
+      const otherLeaf = allowlistLeaf(otherWallet, 0n);
+      const root = allowlistNode(walletLeaf, otherLeaf);
+
+      async function createAllowlistCoin(slots: number) {
+        const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+        const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          "Coin name",
+          "CS",
+          "https://example.org",
+          { root: root, slots: slots }
+        );
+        return {
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
-          coinRecipient: coinRecipientAta,
+          coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+          recipient: wallet.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
-          payer: payer.publicKey,
-        })
-        .instruction();
+        };
+      }
 
-      const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX);
-      await sendAndConfirmTransaction(transaction, wallet, payer);
+      function buyArgs(amount: bigint, allowlistProof: { allocation: anchor.BN; proof: number[][] } | null) {
+        return {
+          amount: new anchor.BN(amount.toString()),
+          maxPay: new anchor.BN(1e11),
+          expiry: null,
+          allowlistProof: allowlistProof,
+        };
+      }
 
-      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
-      const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
-      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
-      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
-      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
-      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee));
+      it("should failed if the allowlist proof is missing", async () => {
+        const accounts = await createAllowlistCoin(1000);
+        try {
+          await program.methods.buy(buyArgs(1n, null)).accountsPartial(accounts).rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6037);
+        }
+      });
 
-      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
-      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
+      it("should failed if the allowlist proof is invalid", async () => {
+        const accounts = await createAllowlistCoin(1000);
+        // claims the leaf of the other wallet, which has no allocation
+        const proof = { allocation: new anchor.BN(0), proof: [Array.from(walletLeaf)] };
+        try {
+          await program.methods.buy(buyArgs(1n, proof)).accountsPartial(accounts).rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6038);
+        }
+      });
 
-      const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
-      expect(coinVaultBalanceAfter.amount + buyAmount).to.eq(MAX_COIN_SUPPLY);
+      it("should failed if the allowlist allocation exceeded", async () => {
+        const accounts = await createAllowlistCoin(1000);
+        const proof = { allocation: new anchor.BN(allocation.toString()), proof: [Array.from(otherLeaf)] };
+        await program.methods.buy(buyArgs(allocation / 2n, proof)).accountsPartial(accounts).rpc();
+        try {
+          await program.methods.buy(buyArgs(allocation / 2n + 1n, proof)).accountsPartial(accounts).rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6039);
+        }
+      });
+
+      it("should succeed with a valid proof", async () => {
+        const accounts = await createAllowlistCoin(1000);
+        const proof = { allocation: new anchor.BN(allocation.toString()), proof: [Array.from(otherLeaf)] };
+        await program.methods.buy(buyArgs(allocation, proof)).accountsPartial(accounts).rpc();
+
+        const coinRecipientAfter = await getAccount(anchor.getProvider().connection, accounts.coinRecipient);
+        expect(coinRecipientAfter.amount).to.eq(allocation);
+      });
+
+      it("should succeed without a proof once the allowlist window ends", async () => {
+        const accounts = await createAllowlistCoin(2);
+        const coin = await program.account.coin.fetch(accounts.coin);
+        while ((await anchor.getProvider().connection.getSlot()) < coin.allowlistEndSlot.toNumber()) {
+          await new Promise((resolve) => setTimeout(resolve, 200));
+        }
+        // the allocation no longer applies either
+        await program.methods.buy(buyArgs(allocation + 1n, null)).accountsPartial(accounts).rpc();
 
-      const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
-      expect(remainingCoinSupply.toNumber() + Number(buyAmount)).to.eq(Number(MAX_COIN_SUPPLY));
-      expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee));
+        const coinRecipientAfter = await getAccount(anchor.getProvider().connection, accounts.coinRecipient);
+        expect(coinRecipientAfter.amount).to.eq(allocation + 1n);
+      });
     });
   });
 
-  describe("#sell", () => {
+  describe("#buy_exact_in", () => {
     it("should failed if fee recipient account mismatch", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
       const { feeRecipientKeypair: feeRecipientKeypairOther } = await initializeConfig();
@@ -1383,18 +2019,21 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sell({
-            amount: new anchor.BN(1e9),
+          .buyExactIn({
+            payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
+            coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypairOther.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPda,
-            solRecipient: wallet.publicKey,
-            coinPayer: coinRecipient.address,
           })
           .rpc();
         expect.fail("should have failed");
@@ -1424,18 +2063,21 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sell({
-            amount: new anchor.BN(1e9),
+          .buyExactIn({
+            payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPdaOther,
+            coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAtaOther,
             solVault: solVaultPdaOther,
-            solRecipient: wallet.publicKey,
-            coinPayer: coinRecipient.address,
           })
           .rpc();
         expect.fail("should have failed");
@@ -1467,18 +2109,21 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sell({
-            amount: new anchor.BN(1e9),
+          .buyExactIn({
+            payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
+            coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAtaOther,
             solVault: solVaultPda,
-            solRecipient: wallet.publicKey,
-            coinPayer: coinRecipient.address,
           })
           .rpc();
         expect.fail("should have failed");
@@ -1510,18 +2155,21 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sell({
-            amount: new anchor.BN(1e9),
+          .buyExactIn({
+            payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
+            coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPdaOther,
-            solRecipient: wallet.publicKey,
-            coinPayer: coinRecipient.address,
           })
           .rpc();
         expect.fail("should have failed");
@@ -1553,27 +2201,29 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sell({
-            amount: new anchor.BN(1e9),
+          .buyExactIn({
+            payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
             coin: coinPda,
+            coinMint: mintKeypairOther.publicKey,
+            coinRecipient: coinRecipient.address,
+            recipient: wallet.publicKey,
             feeRecipient: feeRecipientKeypair.publicKey,
             coinVault: coinVaultAta,
             solVault: solVaultPda,
-            solRecipient: wallet.publicKey,
-            coinPayer: coinRecipient.address,
           })
           .rpc();
         expect.fail("should have failed");
       } catch (e) {
         expect(e instanceof anchor.AnchorError).to.be.true;
         const anchorError = e as anchor.AnchorError;
-        expect(anchorError.logs.join(" ")).to.includes(
-          "Program log: AnchorError occurred. Error Code: ConstraintTokenMint. Error Number: 2014. Error Message: A token mint constraint was violated."
-        );
+        expect(anchorError.error.errorCode.number).to.be.eq(6008);
       }
     });
 
@@ -1596,45 +2246,30 @@ describe("soc-pump-fun", () => {
         payer.publicKey,
         mintKeypair.publicKey
       );
-      const buyAmount = BigInt(1e8) * BigInt(1e6);
-      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
-      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const payAmountWithoutFee = 10n * BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payAmountWithoutFee);
       const buyIX = await program.methods
-        .buy({
-          amount: new anchor.BN(buyAmount.toString()),
-          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+        .buyExactIn({
+          payAmount: new anchor.BN(payAmountWithoutFee.toString()),
+          minReceive: new anchor.BN((buyAmount + 1n).toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
           payer: payer.publicKey,
         })
         .instruction();
-      const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount);
-      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
-      const sellIX = await program.methods
-        .sell({
-          amount: new anchor.BN(buyAmount.toString()),
-          minReceive: new anchor.BN((receiveWithFee - sellFee + 1n).toString()),
-        })
-        .accountsPartial({
-          config: cfgAcctKeypair.publicKey,
-          coin: coinPda,
-          feeRecipient: feeRecipientKeypair.publicKey,
-          coinVault: coinVaultAta,
-          solVault: solVaultPda,
-          solRecipient: wallet.publicKey,
-          payer: payer.publicKey,
-          coinPayer: coinRecipientAta,
-        })
-        .instruction();
 
       const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX, sellIX);
+      transaction.add(createAtaIX, buyIX);
       try {
         await sendAndConfirmTransaction(transaction, wallet, payer);
         expect.fail("should have failed");
@@ -1653,7 +2288,7 @@ describe("soc-pump-fun", () => {
       );
 
       const payer = anchor.web3.Keypair.generate();
-      const payerBalanceBefore = 1e9 * 100;
+      const payerBalanceBefore = 1e9;
       const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
       await confirmTransaction(tx);
 
@@ -1664,18 +2299,21 @@ describe("soc-pump-fun", () => {
         payer.publicKey,
         mintKeypair.publicKey
       );
-      const buyAmount = BigInt(1e8) * BigInt(1e6);
-      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
-      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const payAmountWithoutFee = BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payAmountWithoutFee);
       const buyIX = await program.methods
-        .buy({
-          amount: new anchor.BN(buyAmount.toString()),
-          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+        .buyExactIn({
+          payAmount: new anchor.BN(payAmountWithoutFee.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -1683,43 +2321,19 @@ describe("soc-pump-fun", () => {
         })
         .instruction();
 
-      const coinRecipientAtaOther = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
-      const createAtaOtherIX = createAssociatedTokenAccountInstruction(
-        wallet.publicKey,
-        coinRecipientAtaOther,
-        wallet.publicKey,
-        mintKeypair.publicKey
-      );
-      const sellIX = await program.methods
-        .sell({
-          amount: new anchor.BN(1),
-          minReceive: new anchor.BN(0),
-        })
-        .accountsPartial({
-          config: cfgAcctKeypair.publicKey,
-          coin: coinPda,
-          feeRecipient: feeRecipientKeypair.publicKey,
-          coinVault: coinVaultAta,
-          solVault: solVaultPda,
-          solRecipient: wallet.publicKey,
-          payer: wallet.publicKey,
-          coinPayer: coinRecipientAtaOther,
-        })
-        .instruction();
-
       const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX, createAtaOtherIX, sellIX);
+      transaction.add(createAtaIX, buyIX);
       try {
         await sendAndConfirmTransaction(transaction, wallet, payer);
         expect.fail("should have failed");
       } catch (e) {
         expect(e instanceof anchor.web3.SendTransactionError);
         const sendTxError = e as anchor.web3.SendTransactionError;
-        expect(sendTxError.logs.join(" ").includes("Program log: Error: insufficient funds")).to.be.true;
+        expect(sendTxError.logs.join(" ").includes("insufficient lamports")).to.be.true;
       }
     });
 
-    it("should failed if migration limit exceeded", async () => {
+    it("should not failed if pay too more (sellable coins insufficient)", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
@@ -1731,6 +2345,9 @@ describe("soc-pump-fun", () => {
       const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
       await confirmTransaction(tx);
 
+      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+
       const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
       const createAtaIX = createAssociatedTokenAccountInstruction(
         wallet.publicKey,
@@ -1738,49 +2355,185 @@ describe("soc-pump-fun", () => {
         payer.publicKey,
         mintKeypair.publicKey
       );
-      const payWithoutFee = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
-      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const pay = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
+      const actualPay = pay + BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, actualPay);
+      // only the sol needed to buy the sellable coins is charged
+      const fee = buy_fee(pay, BigInt(cfg.makerFeeRate));
       const buyIX = await program.methods
-        .buy({
-          amount: new anchor.BN(SELLABLE_COINS.toString()),
-          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+        .buyExactIn({
+          payAmount: new anchor.BN(actualPay.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
           payer: payer.publicKey,
         })
         .instruction();
-      const sellIX = await program.methods
-        .sell({
-          amount: new anchor.BN(1),
-          minReceive: new anchor.BN(0),
+
+      const transaction = new anchor.web3.Transaction();
+      transaction.add(createAtaIX, buyIX);
+      await sendAndConfirmTransaction(transaction, wallet, payer);
+
+      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(pay));
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(pay + fee) + userStateRent);
+
+      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
+
+      const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
+      expect(coinVaultBalanceAfter.amount + buyAmount).to.eq(MAX_COIN_SUPPLY);
+
+      const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
+      expect(remainingCoinSupply.toNumber() + Number(buyAmount)).to.eq(Number(MAX_COIN_SUPPLY));
+      expect(accumulateSol.toNumber()).to.eq(Number(pay));
+    });
+
+    it("should only charge the sol needed if the graduation boundary is crossed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9 * 100);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const accounts = {
+        config: cfgAcctKeypair.publicKey,
+        coin: coinPda,
+        coinRecipient: coinRecipientAta,
+        recipient: payer.publicKey,
+        feeRecipient: feeRecipientKeypair.publicKey,
+        coinVault: coinVaultAta,
+        solVault: solVaultPda,
+        payer: payer.publicKey,
+      };
+      const firstPay = 10n * BigInt(1e9);
+      const firstAmount = buy_exact_in(MAX_COIN_SUPPLY, firstPay);
+      const firstBuyIX = await program.methods
+        .buyExactIn({
+          payAmount: new anchor.BN(firstPay.toString()),
+          minReceive: new anchor.BN(firstAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
+        })
+        .accountsPartial(accounts)
+        .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(createAtaIX, firstBuyIX), wallet, payer);
+
+      const remaining = MAX_COIN_SUPPLY - firstAmount;
+      const lastAmount = SELLABLE_COINS - firstAmount;
+      const lastPay = buy(remaining, lastAmount);
+      const lastFee = buy_fee(lastPay, BigInt(cfg.makerFeeRate));
+      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const payerBalanceBefore = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      const lastBuyIX = await program.methods
+        .buyExactIn({
+          payAmount: new anchor.BN((lastPay + BigInt(1e9)).toString()),
+          minReceive: new anchor.BN(lastAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
+        })
+        .accountsPartial(accounts)
+        .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(lastBuyIX), wallet, payer);
+
+      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(lastPay));
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(lastPay + lastFee));
+
+      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientBalanceAfter.amount).to.eq(SELLABLE_COINS);
+
+      const { remainingCoinSupply, accumulateSol, status } = await program.account.coin.fetch(coinPda);
+      expect(remainingCoinSupply.toString()).to.eq((MAX_COIN_SUPPLY - SELLABLE_COINS).toString());
+      expect(accumulateSol.toString()).to.eq((firstPay + lastPay).toString());
+      expect(status).to.be.deep.eq({ complete: {} });
+    });
+
+    it("should failed if migration limit exceeded", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const buyAmount = SELLABLE_COINS;
+      const pay = buy(MAX_COIN_SUPPLY, buyAmount);
+      const actualPay = pay + BigInt(1e9);
+      const buyIX = await program.methods
+        .buyExactIn({
+          payAmount: new anchor.BN(actualPay.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
-          solRecipient: wallet.publicKey,
           payer: payer.publicKey,
-          coinPayer: coinRecipientAta,
         })
         .instruction();
 
       const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX, sellIX);
-      try {
-        await sendAndConfirmTransaction(transaction, wallet, payer);
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
-        const sendTxError = e as anchor.web3.SendTransactionError;
-        expect(sendTxError.message.includes(BigInt(6012).toString(16))).to.be.true;
+      transaction.add(createAtaIX, buyIX);
+      await sendAndConfirmTransaction(transaction, wallet, payer);
+
+      {
+        const transaction = new anchor.web3.Transaction();
+        transaction.add(buyIX);
+        try {
+          await sendAndConfirmTransaction(transaction, wallet, payer);
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+          const sendTxError = e as anchor.web3.SendTransactionError;
+          expect(sendTxError.message.includes(BigInt(6012).toString(16))).to.be.true;
+        }
       }
     });
 
@@ -1806,67 +2559,173 @@ describe("soc-pump-fun", () => {
         payer.publicKey,
         mintKeypair.publicKey
       );
-      const buyAmount = BigInt(1e8) * BigInt(1e6);
-      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const payWithoutFee = 10n * BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
       const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
       const buyIX = await program.methods
-        .buy({
-          amount: new anchor.BN(buyAmount.toString()),
-          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+        .buyExactIn({
+          payAmount: new anchor.BN(payWithoutFee.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
           payer: payer.publicKey,
         })
         .instruction();
-      const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount >> 1n);
-      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
-      const sellIX = await program.methods
-        .sell({
-          amount: new anchor.BN((buyAmount >> 1n).toString()),
-          minReceive: new anchor.BN((receiveWithFee - sellFee).toString()),
-        })
-        .accountsPartial({
-          config: cfgAcctKeypair.publicKey,
-          coin: coinPda,
-          feeRecipient: feeRecipientKeypair.publicKey,
-          coinVault: coinVaultAta,
-          solVault: solVaultPda,
-          solRecipient: payer.publicKey,
-          payer: payer.publicKey,
-          coinPayer: coinRecipientAta,
-        })
-        .instruction();
 
       const transaction = new anchor.web3.Transaction();
-      transaction.add(createAtaIX, buyIX, sellIX);
+      transaction.add(createAtaIX, buyIX);
       await sendAndConfirmTransaction(transaction, wallet, payer);
 
       const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
       const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
-      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee + sellFee));
-      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee - receiveWithFee));
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
       const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
-      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee - (receiveWithFee - sellFee)));
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee) + userStateRent);
 
       const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
-      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount >> 1n);
+      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
 
       const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
-      expect(coinVaultBalanceAfter.amount + (buyAmount >> 1n)).to.eq(MAX_COIN_SUPPLY);
+      expect(coinVaultBalanceAfter.amount + buyAmount).to.eq(MAX_COIN_SUPPLY);
 
       const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
-      expect(remainingCoinSupply.toNumber() + Number(buyAmount >> 1n)).to.eq(Number(MAX_COIN_SUPPLY));
-      expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee - receiveWithFee));
+      expect(remainingCoinSupply.toNumber() + Number(buyAmount)).to.eq(Number(MAX_COIN_SUPPLY));
+      expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee));
+    });
+
+    it("should spend at most the pay amount if the fee is included", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const totalPay = BigInt(1e9) + 7n;
+      const payWithoutFee = buy_pay_amount(totalPay, BigInt(cfg.makerFeeRate));
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      expect(payWithoutFee + fee <= totalPay).to.be.true;
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
+
+      const quote = await program.methods
+        .quoteBuyExactIn({ payAmount: new anchor.BN(totalPay.toString()), feeIncluded: true })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda })
+        .view();
+      expect(quote.solAmount.toString()).to.eq(payWithoutFee.toString());
+      expect(quote.fee.toString()).to.eq(fee.toString());
+      expect(quote.coinAmount.toString()).to.eq(buyAmount.toString());
+
+      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      const buyIX = await program.methods
+        .buyExactIn({
+          payAmount: new anchor.BN(totalPay.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: true,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(createAtaIX, buyIX), wallet, payer);
+
+      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee));
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter - userStateRent <= Number(totalPay)).to.be.true;
+
+      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
+    });
+
+    it("should create the recipient token account if it does not exist", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const recipient = anchor.web3.Keypair.generate();
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, recipient.publicKey);
+      expect(await anchor.getProvider().connection.getAccountInfo(coinRecipientAta)).to.be.null;
+
+      const payWithoutFee = 10n * BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buyExactIn({
+          payAmount: new anchor.BN(payWithoutFee.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: recipient.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(buyIX), wallet, payer);
+
+      // the payer also pays the rent of the new token account and of its user state
+      const rent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(165);
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee) + rent);
+
+      const coinRecipientAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientAfter.owner.toBase58()).to.eq(recipient.publicKey.toBase58());
+      expect(coinRecipientAfter.mint.toBase58()).to.eq(mintKeypair.publicKey.toBase58());
+      expect(coinRecipientAfter.amount).to.eq(buyAmount);
     });
   });
 
-  describe("#sell_exact_out", () => {
+  describe("#sell", () => {
     it("should failed if fee recipient account mismatch", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
       const { feeRecipientKeypair: feeRecipientKeypairOther } = await initializeConfig();
@@ -1883,9 +2742,10 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sellExactOut({
-            receive: new anchor.BN(1e9),
-            maxPay: new anchor.BN(1e9),
+          .sell({
+            amount: new anchor.BN(1e9),
+            minReceive: new anchor.BN(1e9),
+            expiry: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
@@ -1924,9 +2784,10 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sellExactOut({
-            receive: new anchor.BN(1e9),
-            maxPay: new anchor.BN(1e9),
+          .sell({
+            amount: new anchor.BN(1e9),
+            minReceive: new anchor.BN(1e9),
+            expiry: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
@@ -1967,9 +2828,10 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sellExactOut({
-            receive: new anchor.BN(1e9),
-            maxPay: new anchor.BN(1e9),
+          .sell({
+            amount: new anchor.BN(1e9),
+            minReceive: new anchor.BN(1e9),
+            expiry: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
@@ -2010,9 +2872,10 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sellExactOut({
-            receive: new anchor.BN(1e9),
-            maxPay: new anchor.BN(1e9),
+          .sell({
+            amount: new anchor.BN(1e9),
+            minReceive: new anchor.BN(1e9),
+            expiry: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
@@ -2053,9 +2916,10 @@ describe("soc-pump-fun", () => {
       );
       try {
         await program.methods
-          .sellExactOut({
-            receive: new anchor.BN(1e9),
-            maxPay: new anchor.BN(1e9),
+          .sell({
+            amount: new anchor.BN(1e9),
+            minReceive: new anchor.BN(1e9),
+            expiry: null,
           })
           .accountsPartial({
             config: cfgAcctKeypair.publicKey,
@@ -2077,7 +2941,7 @@ describe("soc-pump-fun", () => {
       }
     });
 
-    it("should failed if max pay exceed", async () => {
+    it("should failed if min receive not match", async () => {
       const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
       const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
         cfgAcctKeypair.publicKey,
@@ -2103,25 +2967,27 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
           payer: payer.publicKey,
         })
         .instruction();
-      const receiveWithoutFee = BigInt(1e9);
-      const totalReceive =
-        (receiveWithoutFee * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - BigInt(cfg.takerFeeRate));
-      const amountToSell = sell_exact_out(MAX_COIN_SUPPLY - buyAmount, totalReceive);
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount);
+      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
       const sellIX = await program.methods
-        .sellExactOut({
-          receive: new anchor.BN(receiveWithoutFee.toString()),
-          maxPay: new anchor.BN((amountToSell - 1n).toString()),
+        .sell({
+          amount: new anchor.BN(buyAmount.toString()),
+          minReceive: new anchor.BN((receiveWithFee - sellFee + 1n).toString()),
+          expiry: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
@@ -2143,7 +3009,79 @@ describe("soc-pump-fun", () => {
       } catch (e) {
         expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
         const sendTxError = e as anchor.web3.SendTransactionError;
-        expect(sendTxError.message.includes(BigInt(6010).toString(16))).to.be.true;
+        expect(sendTxError.message.includes(BigInt(6011).toString(16))).to.be.true;
+      }
+    });
+
+    it("should failed if expired", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount);
+      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+      const sellIX = await program.methods
+        .sell({
+          amount: new anchor.BN(buyAmount.toString()),
+          minReceive: new anchor.BN((receiveWithFee - sellFee).toString()),
+          expiry: { unixTimestamp: { unixTimestamp: new anchor.BN(Math.floor(Date.now() / 1000) - 3600) } },
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: wallet.publicKey,
+          payer: payer.publicKey,
+          coinPayer: coinRecipientAta,
+        })
+        .instruction();
+
+      const transaction = new anchor.web3.Transaction();
+      transaction.add(createAtaIX, buyIX, sellIX);
+      try {
+        await sendAndConfirmTransaction(transaction, wallet, payer);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6025).toString(16))).to.be.true;
       }
     });
 
@@ -2173,11 +3111,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -2192,14 +3133,11 @@ describe("soc-pump-fun", () => {
         wallet.publicKey,
         mintKeypair.publicKey
       );
-      const receiveWithoutFee = BigInt(1e9);
-      const totalReceive =
-        (receiveWithoutFee * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - BigInt(cfg.takerFeeRate));
-      const amountToSell = sell_exact_out(MAX_COIN_SUPPLY - buyAmount, totalReceive);
       const sellIX = await program.methods
-        .sellExactOut({
-          receive: new anchor.BN(receiveWithoutFee.toString()),
-          maxPay: new anchor.BN(amountToSell.toString()),
+        .sell({
+          amount: new anchor.BN(1),
+          minReceive: new anchor.BN(0),
+          expiry: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
@@ -2250,11 +3188,14 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(SELLABLE_COINS.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
@@ -2262,9 +3203,10 @@ describe("soc-pump-fun", () => {
         })
         .instruction();
       const sellIX = await program.methods
-        .sellExactOut({
-          receive: new anchor.BN(1),
-          maxPay: new anchor.BN(0),
+        .sell({
+          amount: new anchor.BN(1),
+          minReceive: new anchor.BN(0),
+          expiry: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
@@ -2319,26 +3261,27 @@ describe("soc-pump-fun", () => {
         .buy({
           amount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
           coin: coinPda,
           coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
           feeRecipient: feeRecipientKeypair.publicKey,
           coinVault: coinVaultAta,
           solVault: solVaultPda,
           payer: payer.publicKey,
         })
         .instruction();
-      const receiveWithoutFee = BigInt(1e9);
-      const totalReceive =
-        (receiveWithoutFee * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - BigInt(cfg.takerFeeRate));
-      const sellFee = totalReceive - receiveWithoutFee;
-      const amountToSell = sell_exact_out(MAX_COIN_SUPPLY - buyAmount, totalReceive);
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount >> 1n);
+      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
       const sellIX = await program.methods
-        .sellExactOut({
-          receive: new anchor.BN(receiveWithoutFee.toString()),
-          maxPay: new anchor.BN(amountToSell.toString()),
+        .sell({
+          amount: new anchor.BN((buyAmount >> 1n).toString()),
+          minReceive: new anchor.BN((receiveWithFee - sellFee).toString()),
+          expiry: null,
         })
         .accountsPartial({
           config: cfgAcctKeypair.publicKey,
@@ -2359,38 +3302,2741 @@ describe("soc-pump-fun", () => {
       const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
       const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
       expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee + sellFee));
-      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee - totalReceive));
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee - receiveWithFee));
       const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
-      expect(payerBalanceBefore - payerBalanceAfter).to.eq(Number(payWithoutFee + fee - (totalReceive - sellFee)));
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(
+        Number(payWithoutFee + fee - (receiveWithFee - sellFee)) + userStateRent
+      );
 
       const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
-      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount - amountToSell);
+      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount >> 1n);
 
       const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
-      expect(coinVaultBalanceAfter.amount + (buyAmount - amountToSell)).to.eq(MAX_COIN_SUPPLY);
+      expect(coinVaultBalanceAfter.amount + (buyAmount >> 1n)).to.eq(MAX_COIN_SUPPLY);
 
       const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
-      expect(remainingCoinSupply.toNumber() + Number(buyAmount - amountToSell)).to.eq(Number(MAX_COIN_SUPPLY));
-      expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee - totalReceive));
+      expect(remainingCoinSupply.toNumber() + Number(buyAmount >> 1n)).to.eq(Number(MAX_COIN_SUPPLY));
+      expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee - receiveWithFee));
     });
-  });
 
-  async function initializeConfig(
-    createCoinFee: anchor.BN = new anchor.BN(1e9),
-    makerFeeRate: number = Number((FEE_RATE_BASIS_POINT * 1n) / 100n),
-    takerFeeRate: number = Number((FEE_RATE_BASIS_POINT * 1n) / 100n)
-  ) {
-    const cfgAcctKeypair = anchor.web3.Keypair.generate();
-    const authorityKeypair = anchor.web3.Keypair.generate();
-    const feeRecipientKeypair = anchor.web3.Keypair.generate();
-    const migrationKeypair = anchor.web3.Keypair.generate();
-    let cfg = {
-      authority: authorityKeypair.publicKey,
-      feeRecipient: feeRecipientKeypair.publicKey,
-      migrationAuthority: migrationKeypair.publicKey,
-      createCoinFee: createCoinFee,
-      makerFeeRate: makerFeeRate,
+    describe("during the sell cooldown", () => {
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+
+      async function buyWithCooldown(
+        sellCooldownSlots: number,
+        cooldownSellTaxRate: number,
+        recipient: anchor.web3.PublicKey = wallet.publicKey
+      ) {
+        const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+        await program.methods
+          .updateConfig(
+            updateConfigArgs({ sellCooldownSlots: new anchor.BN(sellCooldownSlots), cooldownSellTaxRate })
+          )
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey
+        );
+        const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, recipient);
+        await program.methods
+          .buy({
+            amount: new anchor.BN(buyAmount.toString()),
+            maxPay: new anchor.BN(1e11),
+            expiry: null,
+            allowlistProof: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinRecipient: coinRecipientAta,
+            recipient: recipient,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+          })
+          .rpc();
+        const sellAccounts = {
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: recipient,
+          coinPayer: coinRecipientAta,
+          payer: recipient,
+        };
+        return { cfg, coinPda, feeRecipient: feeRecipientKeypair.publicKey, sellAccounts };
+      }
+
+      function sellArgs(amount: bigint) {
+        return { amount: new anchor.BN(amount.toString()), minReceive: new anchor.BN(0), expiry: null };
+      }
+
+      it("should failed if the sell is rejected", async () => {
+        const { sellAccounts } = await buyWithCooldown(1000, 0);
+        try {
+          await program.methods.sell(sellArgs(buyAmount)).accountsPartial(sellAccounts).rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6042);
+        }
+      });
+
+      it("should failed if the coins were bought for another wallet", async () => {
+        // the cooldown follows the wallet receiving the coins, not the payer of the buy
+        const recipient = anchor.web3.Keypair.generate();
+        const { sellAccounts } = await buyWithCooldown(1000, 0, recipient.publicKey);
+        try {
+          await program.methods.sell(sellArgs(buyAmount)).accountsPartial(sellAccounts).signers([recipient]).rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6042);
+        }
+      });
+
+      it("should charge the cooldown sell tax", async () => {
+        const cooldownSellTaxRate = FEE_RATE_BASIS_POINT / 10n;
+        const { cfg, feeRecipient, sellAccounts } = await buyWithCooldown(1000, Number(cooldownSellTaxRate));
+
+        const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount);
+        const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate) + cooldownSellTaxRate);
+        const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipient);
+        await program.methods.sell(sellArgs(buyAmount)).accountsPartial(sellAccounts).rpc();
+
+        const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipient);
+        expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(sellFee));
+      });
+
+      it("should succeed once the cooldown ends", async () => {
+        const { cfg, coinPda, feeRecipient, sellAccounts } = await buyWithCooldown(2, 0);
+        const [userStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from(USER_STATE_SEED), coinPda.toBuffer(), wallet.publicKey.toBuffer()],
+          program.programId
+        );
+        const userState = await program.account.userState.fetch(userStatePda);
+        while ((await anchor.getProvider().connection.getSlot()) < userState.lastBuySlot.toNumber() + 2) {
+          await new Promise((resolve) => setTimeout(resolve, 200));
+        }
+
+        const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, buyAmount);
+        const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+        const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipient);
+        await program.methods.sell(sellArgs(buyAmount)).accountsPartial(sellAccounts).rpc();
+
+        const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipient);
+        expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(sellFee));
+      });
+    });
+  });
+
+  describe("#sell_exact_out", () => {
+    it("should failed if fee recipient account mismatch", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { feeRecipientKeypair: feeRecipientKeypairOther } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .sellExactOut({
+            receive: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            feeRecipient: feeRecipientKeypairOther.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            solRecipient: wallet.publicKey,
+            coinPayer: coinRecipient.address,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6003);
+      }
+    });
+
+    it("should failed if config account mismatch", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
+        await initializeConfig();
+      const {
+        mintKeypair: mintKeypairOther,
+        coinPda: coinPdaOther,
+        coinVaultAta: coinVaultAtaOther,
+        solVaultPda: solVaultPdaOther,
+      } = await createCoin(cfgAcctKeypairOther.publicKey, feeRecipientKeypairOther.publicKey);
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypairOther.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .sellExactOut({
+            receive: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPdaOther,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAtaOther,
+            solVault: solVaultPdaOther,
+            solRecipient: wallet.publicKey,
+            coinPayer: coinRecipient.address,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6006);
+      }
+    });
+
+    it("should failed if coin vault account mismatch", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
+        await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const { coinVaultAta: coinVaultAtaOther } = await createCoin(
+        cfgAcctKeypairOther.publicKey,
+        feeRecipientKeypairOther.publicKey
+      );
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .sellExactOut({
+            receive: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAtaOther,
+            solVault: solVaultPda,
+            solRecipient: wallet.publicKey,
+            coinPayer: coinRecipient.address,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6007);
+      }
+    });
+
+    it("should failed if sol vault account mismatch", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
+        await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const { solVaultPda: solVaultPdaOther } = await createCoin(
+        cfgAcctKeypairOther.publicKey,
+        feeRecipientKeypairOther.publicKey
+      );
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .sellExactOut({
+            receive: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPdaOther,
+            solRecipient: wallet.publicKey,
+            coinPayer: coinRecipient.address,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6004);
+      }
+    });
+
+    it("should failed if coin mint account mismatch", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { cfgAcctKeypair: cfgAcctKeypairOther, feeRecipientKeypair: feeRecipientKeypairOther } =
+        await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const { mintKeypair: mintKeypairOther } = await createCoin(
+        cfgAcctKeypairOther.publicKey,
+        feeRecipientKeypairOther.publicKey
+      );
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypairOther.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .sellExactOut({
+            receive: new anchor.BN(1e9),
+            maxPay: new anchor.BN(1e9),
+            expiry: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            solRecipient: wallet.publicKey,
+            coinPayer: coinRecipient.address,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.logs.join(" ")).to.includes(
+          "Program log: AnchorError occurred. Error Code: ConstraintTokenMint. Error Number: 2014. Error Message: A token mint constraint was violated."
+        );
+      }
+    });
+
+    it("should failed if max pay exceed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+      const receiveWithoutFee = BigInt(1e9);
+      const totalReceive =
+        (receiveWithoutFee * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - BigInt(cfg.takerFeeRate));
+      const amountToSell = sell_exact_out(MAX_COIN_SUPPLY - buyAmount, totalReceive);
+      const sellIX = await program.methods
+        .sellExactOut({
+          receive: new anchor.BN(receiveWithoutFee.toString()),
+          maxPay: new anchor.BN((amountToSell - 1n).toString()),
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: wallet.publicKey,
+          payer: payer.publicKey,
+          coinPayer: coinRecipientAta,
+        })
+        .instruction();
+
+      const transaction = new anchor.web3.Transaction();
+      transaction.add(createAtaIX, buyIX, sellIX);
+      try {
+        await sendAndConfirmTransaction(transaction, wallet, payer);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6010).toString(16))).to.be.true;
+      }
+    });
+
+    it("should failed if payer balance insufficient", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+
+      const coinRecipientAtaOther = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      const createAtaOtherIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAtaOther,
+        wallet.publicKey,
+        mintKeypair.publicKey
+      );
+      const receiveWithoutFee = BigInt(1e9);
+      const totalReceive =
+        (receiveWithoutFee * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - BigInt(cfg.takerFeeRate));
+      const amountToSell = sell_exact_out(MAX_COIN_SUPPLY - buyAmount, totalReceive);
+      const sellIX = await program.methods
+        .sellExactOut({
+          receive: new anchor.BN(receiveWithoutFee.toString()),
+          maxPay: new anchor.BN(amountToSell.toString()),
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: wallet.publicKey,
+          payer: wallet.publicKey,
+          coinPayer: coinRecipientAtaOther,
+        })
+        .instruction();
+
+      const transaction = new anchor.web3.Transaction();
+      transaction.add(createAtaIX, buyIX, createAtaOtherIX, sellIX);
+      try {
+        await sendAndConfirmTransaction(transaction, wallet, payer);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError);
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.logs.join(" ").includes("Program log: Error: insufficient funds")).to.be.true;
+      }
+    });
+
+    it("should failed if migration limit exceeded", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buy({
+          amount: new anchor.BN(SELLABLE_COINS.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+      const sellIX = await program.methods
+        .sellExactOut({
+          receive: new anchor.BN(1),
+          maxPay: new anchor.BN(0),
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: wallet.publicKey,
+          payer: payer.publicKey,
+          coinPayer: coinRecipientAta,
+        })
+        .instruction();
+
+      const transaction = new anchor.web3.Transaction();
+      transaction.add(createAtaIX, buyIX, sellIX);
+      try {
+        await sendAndConfirmTransaction(transaction, wallet, payer);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6012).toString(16))).to.be.true;
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9 * 100;
+      const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+
+      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, payer.publicKey);
+      const createAtaIX = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        coinRecipientAta,
+        payer.publicKey,
+        mintKeypair.publicKey
+      );
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: payer.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: payer.publicKey,
+        })
+        .instruction();
+      const receiveWithoutFee = BigInt(1e9);
+      const totalReceive =
+        (receiveWithoutFee * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - BigInt(cfg.takerFeeRate));
+      const sellFee = totalReceive - receiveWithoutFee;
+      const amountToSell = sell_exact_out(MAX_COIN_SUPPLY - buyAmount, totalReceive);
+      const sellIX = await program.methods
+        .sellExactOut({
+          receive: new anchor.BN(receiveWithoutFee.toString()),
+          maxPay: new anchor.BN(amountToSell.toString()),
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: payer.publicKey,
+          payer: payer.publicKey,
+          coinPayer: coinRecipientAta,
+        })
+        .instruction();
+
+      const transaction = new anchor.web3.Transaction();
+      transaction.add(createAtaIX, buyIX, sellIX);
+      await sendAndConfirmTransaction(transaction, wallet, payer);
+
+      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee + sellFee));
+      expect(solVaultBalanceAfter - solVaultBalanceBefore).to.eq(Number(payWithoutFee - totalReceive));
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(payerBalanceBefore - payerBalanceAfter).to.eq(
+        Number(payWithoutFee + fee - (totalReceive - sellFee)) + userStateRent
+      );
+
+      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount - amountToSell);
+
+      const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
+      expect(coinVaultBalanceAfter.amount + (buyAmount - amountToSell)).to.eq(MAX_COIN_SUPPLY);
+
+      const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(coinPda);
+      expect(remainingCoinSupply.toNumber() + Number(buyAmount - amountToSell)).to.eq(Number(MAX_COIN_SUPPLY));
+      expect(accumulateSol.toNumber()).to.eq(Number(payWithoutFee - totalReceive));
+    });
+  });
+
+  describe("#quote_buy", () => {
+    it("should quote a buy without moving funds", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { coinPda, solVaultPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const solVaultBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+
+      const buyAmount = BigInt(1e12);
+      const quote = await program.methods
+        .quoteBuy({ amount: new anchor.BN(buyAmount.toString()) })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda })
+        .view();
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      expect(quote.coinAmount.toString()).to.be.eq(buyAmount.toString());
+      expect(quote.solAmount.toString()).to.be.eq(payWithoutFee.toString());
+      expect(quote.fee.toString()).to.be.eq(buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate)).toString());
+      expect(quote.remainingCoinSupply.toString()).to.be.eq((MAX_COIN_SUPPLY - buyAmount).toString());
+      expect(quote.accumulateSol.toString()).to.be.eq(payWithoutFee.toString());
+      expect(quote.complete).to.be.false;
+
+      const coin = await program.account.coin.fetch(coinPda);
+      expect(coin.remainingCoinSupply.toString()).to.be.eq(MAX_COIN_SUPPLY.toString());
+      expect(await anchor.getProvider().connection.getBalance(solVaultPda)).to.be.eq(solVaultBefore);
+    });
+
+    it("should quote the buy completing the coin", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const quote = await program.methods
+        .quoteBuy({ amount: new anchor.BN(SELLABLE_COINS.toString()) })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda })
+        .view();
+      expect(quote.solAmount.toString()).to.be.eq(buy(MAX_COIN_SUPPLY, SELLABLE_COINS).toString());
+      expect(quote.remainingCoinSupply.toString()).to.be.eq(REVERSE_COINS.toString());
+      expect(quote.complete).to.be.true;
+    });
+  });
+
+  describe("#quote_buy_exact_in", () => {
+    it("should quote a buy paying the exact sol", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const payWithoutFee = BigInt(1e9);
+      const quote = await program.methods
+        .quoteBuyExactIn({ payAmount: new anchor.BN(payWithoutFee.toString()), feeIncluded: false })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda })
+        .view();
+      expect(quote.coinAmount.toString()).to.be.eq(buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee).toString());
+      expect(quote.solAmount.toString()).to.be.eq(payWithoutFee.toString());
+      expect(quote.fee.toString()).to.be.eq(buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate)).toString());
+      expect(quote.complete).to.be.false;
+    });
+
+    it("should clamp the quote to the available supply", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const quote = await program.methods
+        .quoteBuyExactIn({ payAmount: new anchor.BN(1e9 * 100), feeIncluded: false })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda })
+        .view();
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
+      expect(quote.coinAmount.toString()).to.be.eq(SELLABLE_COINS.toString());
+      expect(quote.solAmount.toString()).to.be.eq(payWithoutFee.toString());
+      expect(quote.fee.toString()).to.be.eq(buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate)).toString());
+      expect(quote.complete).to.be.true;
+    });
+
+    it("should quote without the fee included if it is omitted by an older client", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const simulate = async (ix: anchor.web3.TransactionInstruction) => {
+        const tx = new anchor.web3.Transaction().add(ix);
+        tx.feePayer = wallet.publicKey;
+        const { value } = await anchor.getProvider().connection.simulateTransaction(tx, [wallet]);
+        expect(value.err).to.be.null;
+        return value.returnData.data[0];
+      };
+      const quoteIX = await program.methods
+        .quoteBuyExactIn({ payAmount: new anchor.BN(1e9), feeIncluded: false })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda })
+        .instruction();
+      const quote = await simulate(quoteIX);
+      // drop the `fee_included` flag, which is what an older client sends
+      quoteIX.data = quoteIX.data.subarray(0, quoteIX.data.length - 1);
+      expect(await simulate(quoteIX)).to.be.eq(quote);
+    });
+  });
+
+  describe("#quote_sell", () => {
+    it("should quote a sell without moving funds", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+      const coinBefore = await program.account.coin.fetch(coinPda);
+
+      const quote = await program.methods
+        .quoteSell({ amount: new anchor.BN(coinAmount.toString()) })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda, seller: wallet.publicKey })
+        .view();
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - coinAmount, coinAmount);
+      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+      expect(quote.coinAmount.toString()).to.be.eq(coinAmount.toString());
+      expect(quote.solAmount.toString()).to.be.eq((receiveWithFee - sellFee).toString());
+      expect(quote.fee.toString()).to.be.eq(sellFee.toString());
+      expect(quote.remainingCoinSupply.toString()).to.be.eq(MAX_COIN_SUPPLY.toString());
+      expect(quote.complete).to.be.false;
+
+      const coinAfter = await program.account.coin.fetch(coinPda);
+      expect(coinAfter.remainingCoinSupply.toString()).to.be.eq(coinBefore.remainingCoinSupply.toString());
+      expect(coinAfter.accumulateSol.toString()).to.be.eq(coinBefore.accumulateSol.toString());
+    });
+  });
+
+  describe("#quote_sell_exact_out", () => {
+    it("should quote a sell receiving the exact sol", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+
+      const receive = BigInt(1e8);
+      const quote = await program.methods
+        .quoteSellExactOut({ receive: new anchor.BN(receive.toString()) })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda, seller: wallet.publicKey })
+        .view();
+      const takerFeeRate = BigInt(cfg.takerFeeRate);
+      const totalReceive = (receive * FEE_RATE_BASIS_POINT) / (FEE_RATE_BASIS_POINT - takerFeeRate);
+      expect(quote.coinAmount.toString()).to.be.eq(
+        sell_exact_out(MAX_COIN_SUPPLY - coinAmount, totalReceive).toString()
+      );
+      expect(quote.solAmount.toString()).to.be.eq(receive.toString());
+      expect(quote.fee.toString()).to.be.eq((totalReceive - receive).toString());
+    });
+  });
+
+  describe("#migrate", () => {
+    it("should failed if migration authority mismatch", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      await buyAllCoins(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey, mintKeypair.publicKey, cfg);
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .migrate()
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            coinRecipient: coinRecipient.address,
+            migrationAuthority: wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6016);
+      }
+    });
+
+    it("should failed if bonding curve not completed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, migrationKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        migrationKeypair.publicKey
+      );
+      try {
+        await program.methods
+          .migrate()
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            coinRecipient: coinRecipient.address,
+            migrationAuthority: migrationKeypair.publicKey,
+          })
+          .signers([migrationKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6017);
+      }
+    });
+
+    it("should succeed and failed if migrate again", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, migrationKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      await buyAllCoins(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey, mintKeypair.publicKey, cfg);
+
+      const tx = await anchor.getProvider().connection.requestAirdrop(migrationKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        migrationKeypair.publicKey
+      );
+      const solVaultBalanceBefore = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const authorityBalanceBefore = await anchor.getProvider().connection.getBalance(migrationKeypair.publicKey);
+      const migrateIX = await program.methods
+        .migrate()
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          coinRecipient: coinRecipient.address,
+          migrationAuthority: migrationKeypair.publicKey,
+        })
+        .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(migrateIX), wallet, migrationKeypair);
+
+      const solVaultBalanceAfter = await anchor.getProvider().connection.getBalance(solVaultPda);
+      const authorityBalanceAfter = await anchor.getProvider().connection.getBalance(migrationKeypair.publicKey);
+      expect(solVaultBalanceAfter).to.eq(0);
+      expect(authorityBalanceAfter - authorityBalanceBefore).to.eq(solVaultBalanceBefore);
+
+      const coinRecipientAfter = await getAccount(anchor.getProvider().connection, coinRecipient.address);
+      expect(coinRecipientAfter.amount).to.eq(MAX_COIN_SUPPLY - SELLABLE_COINS);
+      const coinVaultAfter = await getAccount(anchor.getProvider().connection, coinVaultAta);
+      expect(coinVaultAfter.amount).to.eq(0n);
+
+      const { status } = await program.account.coin.fetch(coinPda);
+      expect(status).to.be.deep.eq({ migrated: {} });
+
+      try {
+        await sendAndConfirmTransaction(new anchor.web3.Transaction().add(migrateIX), wallet, migrationKeypair);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6018).toString(16))).to.be.true;
+      }
+    });
+  });
+
+  describe("#create_pool", () => {
+    it("should failed if bonding curve not completed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, migrationKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const tx = await anchor.getProvider().connection.requestAirdrop(migrationKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+
+      try {
+        await program.methods
+          .createPool()
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            pool: poolPda(mintKeypair.publicKey),
+            solVault: solVaultPda,
+            migrationAuthority: migrationKeypair.publicKey,
+          })
+          .signers([migrationKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6017);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, migrationKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      await buyAllCoins(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey, mintKeypair.publicKey, cfg);
+      const tx = await anchor.getProvider().connection.requestAirdrop(migrationKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+
+      await program.methods
+        .createPool()
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          pool: poolPda(mintKeypair.publicKey),
+          solVault: solVaultPda,
+          migrationAuthority: migrationKeypair.publicKey,
+        })
+        .signers([migrationKeypair])
+        .rpc();
+
+      const pool = await program.account.pool.fetch(poolPda(mintKeypair.publicKey));
+      const { accumulateSol, status } = await program.account.coin.fetch(coinPda);
+      expect(pool.coin.toBase58()).to.be.eq(coinPda.toBase58());
+      expect(pool.solReserve.toString()).to.be.eq(accumulateSol.toString());
+      expect(pool.coinReserve.toString()).to.be.eq((MAX_COIN_SUPPLY - SELLABLE_COINS).toString());
+      expect(status).to.be.deep.eq({ migrated: {} });
+    });
+  });
+
+  describe("#pool_swap", () => {
+    it("should failed if min receive not match", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, mintKeypair, coinPda, coinVaultAta, solVaultPda, payer, coinAta } =
+        await createCoinWithPool();
+      const pool = await program.account.pool.fetch(poolPda(mintKeypair.publicKey));
+      const payWithoutFee = BigInt(1e9);
+      const receive = swap(BigInt(pool.solReserve.toString()), BigInt(pool.coinReserve.toString()), payWithoutFee);
+
+      try {
+        await program.methods
+          .poolSwap({
+            side: { buy: {} },
+            amountIn: new anchor.BN(payWithoutFee.toString()),
+            minReceive: new anchor.BN((receive + 1n).toString()),
+            expiry: null,
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            pool: poolPda(mintKeypair.publicKey),
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            coinAccount: coinAta,
+            payer: payer.publicKey,
+          })
+          .signers([payer])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6011);
+      }
+    });
+
+    it("should failed if expired", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, mintKeypair, coinPda, coinVaultAta, solVaultPda, payer, coinAta } =
+        await createCoinWithPool();
+      try {
+        await program.methods
+          .poolSwap({
+            side: { buy: {} },
+            amountIn: new anchor.BN(1e9),
+            minReceive: new anchor.BN(0),
+            expiry: { unixTimestamp: { unixTimestamp: new anchor.BN(Math.floor(Date.now() / 1000) - 3600) } },
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            pool: poolPda(mintKeypair.publicKey),
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            coinAccount: coinAta,
+            payer: payer.publicKey,
+          })
+          .signers([payer])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6025);
+      }
+    });
+
+    it("should succeed to buy and sell", async () => {
+      const {
+        cfgAcctKeypair,
+        feeRecipientKeypair,
+        cfg,
+        mintKeypair,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        payer,
+        coinAta,
+      } = await createCoinWithPool();
+      const accounts = {
+        config: cfgAcctKeypair.publicKey,
+        coin: coinPda,
+        pool: poolPda(mintKeypair.publicKey),
+        feeRecipient: feeRecipientKeypair.publicKey,
+        coinVault: coinVaultAta,
+        solVault: solVaultPda,
+        coinAccount: coinAta,
+        payer: payer.publicKey,
+      };
+
+      // buy
+      const poolBefore = await program.account.pool.fetch(poolPda(mintKeypair.publicKey));
+      const solReserve = BigInt(poolBefore.solReserve.toString());
+      const coinReserve = BigInt(poolBefore.coinReserve.toString());
+      const payWithoutFee = BigInt(1e9);
+      const buyFee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyAmount = swap(solReserve, coinReserve, payWithoutFee);
+      const coinBefore = await getAccount(anchor.getProvider().connection, coinAta);
+      const payerBalanceBefore = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      await program.methods
+        .poolSwap({
+          side: { buy: {} },
+          amountIn: new anchor.BN(payWithoutFee.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+        })
+        .accountsPartial(accounts)
+        .signers([payer])
+        .rpc();
+      const coinAfterBuy = await getAccount(anchor.getProvider().connection, coinAta);
+      const payerBalanceAfterBuy = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      expect(coinAfterBuy.amount - coinBefore.amount).to.eq(buyAmount);
+      expect(payerBalanceBefore - payerBalanceAfterBuy).to.eq(Number(payWithoutFee + buyFee));
+
+      // sell
+      const totalReceive = swap(coinReserve - buyAmount, solReserve + payWithoutFee, buyAmount);
+      const sellFee = sell_fee(totalReceive, BigInt(cfg.takerFeeRate));
+      await program.methods
+        .poolSwap({
+          side: { sell: {} },
+          amountIn: new anchor.BN(buyAmount.toString()),
+          minReceive: new anchor.BN((totalReceive - sellFee).toString()),
+          expiry: null,
+        })
+        .accountsPartial(accounts)
+        .signers([payer])
+        .rpc();
+      const coinAfterSell = await getAccount(anchor.getProvider().connection, coinAta);
+      const payerBalanceAfterSell = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      expect(coinAfterSell.amount).to.eq(coinBefore.amount);
+      expect(payerBalanceAfterSell - payerBalanceAfterBuy).to.eq(Number(totalReceive - sellFee));
+
+      const poolAfter = await program.account.pool.fetch(poolPda(mintKeypair.publicKey));
+      expect(poolAfter.solReserve.toString()).to.be.eq((solReserve + payWithoutFee - totalReceive).toString());
+      expect(poolAfter.coinReserve.toString()).to.be.eq(coinReserve.toString());
+    });
+  });
+
+  describe("#set_halted", () => {
+    it("should failed if not the authority", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      try {
+        await program.methods
+          .setHalted({ halted: true })
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda, authority: wallet.publicKey })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6002);
+      }
+    });
+
+    it("should failed to halt a completed coin", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, authorityKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      await buyAllCoins(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey, mintKeypair.publicKey, cfg);
+      const { status } = await program.account.coin.fetch(coinPda);
+      expect(status).to.be.deep.eq({ complete: {} });
+
+      try {
+        await program.methods
+          .setHalted({ halted: true })
+          .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda, authority: authorityKeypair.publicKey })
+          .signers([wallet, authorityKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6021);
+      }
+    });
+
+    it("should reject trades while halted and accept them after resumed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, authorityKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      await program.methods
+        .setHalted({ halted: true })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      expect((await program.account.coin.fetch(coinPda)).status).to.be.deep.eq({ halted: {} });
+
+      const coinRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      const buyAmount = BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const buyIX = await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipient.address,
+          recipient: wallet.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          payer: wallet.publicKey,
+        })
+        .instruction();
+      try {
+        await sendAndConfirmTransaction(new anchor.web3.Transaction().add(buyIX), wallet);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6020).toString(16))).to.be.true;
+      }
+
+      await program.methods
+        .setHalted({ halted: false })
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, coin: coinPda, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(buyIX), wallet);
+      const coinRecipientAfter = await getAccount(anchor.getProvider().connection, coinRecipient.address);
+      expect(coinRecipientAfter.amount).to.eq(buyAmount);
+      expect((await program.account.coin.fetch(coinPda)).status).to.be.deep.eq({ trading: {} });
+    });
+  });
+
+  describe("#create_and_buy", () => {
+    it("should failed if creator buy limit exceeded", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxCreatorBuyBps: 100 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, accounts, coinRecipientAta } = createCoinAccounts(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const buyAmount = (MAX_COIN_SUPPLY * 100n) / 10000n + 1n;
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      try {
+        await program.methods
+          .createAndBuy({
+            create: {
+              name: "Coin name",
+              symbol: "CS",
+              uri: "https://example.org",
+              allowlistRoot: null,
+              allowlistSlots: new anchor.BN(0),
+              curveKind: { constantProduct: {} },
+              decimals: DECIMALS,
+            },
+            buy: {
+              payAmount: new anchor.BN(payWithoutFee.toString()),
+              minReceive: new anchor.BN(buyAmount.toString()),
+              expiry: null,
+              allowlistProof: null,
+              feeIncluded: false,
+            },
+          })
+          .accountsPartial({
+            create: accounts,
+            coinRecipient: coinRecipientAta,
+            userState: userStatePda(accounts.coin, wallet.publicKey),
+          })
+          .signers([wallet, mintKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6023);
+      }
+    });
+
+    it("should failed if an allowlist proof is given", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, accounts, coinRecipientAta } = createCoinAccounts(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      try {
+        await program.methods
+          .createAndBuy({
+            create: {
+              name: "Coin name",
+              symbol: "CS",
+              uri: "https://example.org",
+              allowlistRoot: null,
+              allowlistSlots: new anchor.BN(0),
+              curveKind: { constantProduct: {} },
+              decimals: DECIMALS,
+            },
+            buy: {
+              payAmount: new anchor.BN(1e9),
+              minReceive: new anchor.BN(0),
+              expiry: null,
+              allowlistProof: { allocation: new anchor.BN(0), proof: [] },
+              feeIncluded: false,
+            },
+          })
+          .accountsPartial({
+            create: accounts,
+            coinRecipient: coinRecipientAta,
+            userState: userStatePda(accounts.coin, wallet.publicKey),
+          })
+          .signers([wallet, mintKeypair])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6038);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxCreatorBuyBps: 1000 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, accounts, coinRecipientAta } = createCoinAccounts(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+
+      const payWithoutFee = 10n * BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      await program.methods
+        .createAndBuy({
+          create: {
+            name: "Coin name",
+            symbol: "CS",
+            uri: "https://example.org",
+            allowlistRoot: null,
+            allowlistSlots: new anchor.BN(0),
+            curveKind: { constantProduct: {} },
+            decimals: DECIMALS,
+          },
+          buy: {
+            payAmount: new anchor.BN(payWithoutFee.toString()),
+            minReceive: new anchor.BN(buyAmount.toString()),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
+          },
+        })
+        .accountsPartial({
+          create: accounts,
+          coinRecipient: coinRecipientAta,
+          userState: userStatePda(accounts.coin, wallet.publicKey),
+        })
+        .signers([wallet, mintKeypair])
+        .rpc();
+
+      const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(
+        Number(BigInt(cfg.createCoinFee.toString()) + fee)
+      );
+
+      const coinRecipientBalanceAfter = await getAccount(anchor.getProvider().connection, coinRecipientAta);
+      expect(coinRecipientBalanceAfter.amount).to.eq(buyAmount);
+      const coinVaultBalanceAfter = await getAccount(anchor.getProvider().connection, accounts.coinVault);
+      expect(coinVaultBalanceAfter.amount + buyAmount).to.eq(MAX_COIN_SUPPLY);
+
+      const { remainingCoinSupply, accumulateSol } = await program.account.coin.fetch(accounts.coin);
+      expect(remainingCoinSupply.toString()).to.eq((MAX_COIN_SUPPLY - buyAmount).toString());
+      expect(accumulateSol.toString()).to.eq(payWithoutFee.toString());
+
+      const userState = await program.account.userState.fetch(userStatePda(accounts.coin, wallet.publicKey));
+      expect(userState.bought.toString()).to.eq(buyAmount.toString());
+    });
+  });
+
+  describe("#sell_fraction", () => {
+    it("should failed if fraction is invalid", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+
+      for (const fractionBps of [0, 10001]) {
+        try {
+          await program.methods
+            .sellFraction({ fractionBps, minReceive: new anchor.BN(0), closeIfEmpty: false, expiry: null })
+            .accountsPartial({
+              config: cfgAcctKeypair.publicKey,
+              coin: coinPda,
+              feeRecipient: feeRecipientKeypair.publicKey,
+              coinVault: coinVaultAta,
+              solVault: solVaultPda,
+              solRecipient: wallet.publicKey,
+              coinPayer: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+            })
+            .rpc();
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const anchorError = e as anchor.AnchorError;
+          expect(anchorError.error.errorCode.number).to.be.eq(6032);
+        }
+      }
+    });
+
+    it("should succeed to sell half", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+
+      const amountToSell = (coinAmount * 5000n) / 10000n;
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - coinAmount, amountToSell);
+      const receive = receiveWithFee - sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+      const solRecipient = anchor.web3.Keypair.generate();
+      const coinPayer = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      await program.methods
+        .sellFraction({
+          fractionBps: 5000,
+          minReceive: new anchor.BN(receive.toString()),
+          closeIfEmpty: true,
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: solRecipient.publicKey,
+          coinPayer: coinPayer,
+        })
+        .rpc();
+
+      const coinPayerAfter = await getAccount(anchor.getProvider().connection, coinPayer);
+      expect(coinPayerAfter.amount).to.eq(coinAmount - amountToSell);
+      const solRecipientBalance = await anchor.getProvider().connection.getBalance(solRecipient.publicKey);
+      expect(solRecipientBalance).to.eq(Number(receive));
+    });
+
+    it("should succeed to sell all and close the coin payer", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - coinAmount, coinAmount);
+      const receive = receiveWithFee - sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+      const coinPayer = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      const coinPayerInfo = await anchor.getProvider().connection.getAccountInfo(coinPayer);
+      const sellIX = await program.methods
+        .sellFraction({
+          fractionBps: 10000,
+          minReceive: new anchor.BN(receive.toString()),
+          closeIfEmpty: true,
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: wallet.publicKey,
+          coinPayer: coinPayer,
+          payer: wallet.publicKey,
+        })
+        .instruction();
+      const walletBalanceBefore = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(sellIX), wallet);
+
+      // the seller receives the sol and the rent of the coin payer
+      const walletBalanceAfter = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      expect(walletBalanceAfter - walletBalanceBefore).to.eq(Number(receive) + coinPayerInfo.lamports - 5000);
+      expect(await anchor.getProvider().connection.getAccountInfo(coinPayer)).to.be.null;
+    });
+  });
+
+  describe("#swap_coins", () => {
+    it("should failed if coins are identical", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAta = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .swapCoins({ amount: new anchor.BN(1), minReceive: new anchor.BN(0), expiry: null })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinA: coinPda,
+            coinAVault: coinVaultAta,
+            coinASolVault: solVaultPda,
+            coinAPayer: coinAta.address,
+            coinB: coinPda,
+            coinBVault: coinVaultAta,
+            coinBSolVault: solVaultPda,
+            coinBRecipient: coinAta.address,
+            coinBUserState: userStatePda(coinPda, wallet.publicKey),
+            payer: wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6024);
+      }
+    });
+
+    it("should failed if expired", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const coinA = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const coinB = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const coinAAta = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        coinA.mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      const coinBAta = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        coinB.mintKeypair.publicKey,
+        wallet.publicKey
+      );
+      try {
+        await program.methods
+          .swapCoins({
+            amount: new anchor.BN(1),
+            minReceive: new anchor.BN(0),
+            expiry: { unixTimestamp: { unixTimestamp: new anchor.BN(Math.floor(Date.now() / 1000) - 3600) } },
+          })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinA: coinA.coinPda,
+            coinAVault: coinA.coinVaultAta,
+            coinASolVault: coinA.solVaultPda,
+            coinAPayer: coinAAta.address,
+            coinB: coinB.coinPda,
+            coinBVault: coinB.coinVaultAta,
+            coinBSolVault: coinB.solVaultPda,
+            coinBRecipient: coinBAta.address,
+            coinBUserState: userStatePda(coinB.coinPda, wallet.publicKey),
+            payer: wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6025);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const coinA = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const coinB = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const coinAAta = getAssociatedTokenAddressSync(coinA.mintKeypair.publicKey, wallet.publicKey);
+      const coinBAta = getAssociatedTokenAddressSync(coinB.mintKeypair.publicKey, wallet.publicKey);
+      const payWithoutFee = 10n * BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
+      const buyIX = await program.methods
+        .buyExactIn({
+          payAmount: new anchor.BN(payWithoutFee.toString()),
+          minReceive: new anchor.BN(buyAmount.toString()),
+          expiry: null,
+          allowlistProof: null,
+          feeIncluded: false,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinA.coinPda,
+          coinRecipient: coinAAta,
+          recipient: wallet.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinA.coinVaultAta,
+          solVault: coinA.solVaultPda,
+          payer: wallet.publicKey,
+        })
+        .instruction();
+      const transaction = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          coinAAta,
+          wallet.publicKey,
+          coinA.mintKeypair.publicKey
+        ),
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          coinBAta,
+          wallet.publicKey,
+          coinB.mintKeypair.publicKey
+        ),
+        buyIX
+      );
+      await sendAndConfirmTransaction(transaction, wallet);
+
+      const amountToSell = buyAmount / 2n;
+      const totalReceive = sell(MAX_COIN_SUPPLY - buyAmount, amountToSell);
+      const receive = totalReceive - sell_fee(totalReceive, BigInt(cfg.takerFeeRate));
+      const payAmount = buy_pay_amount(receive, BigInt(cfg.makerFeeRate));
+      const expectReceive = buy_exact_in(MAX_COIN_SUPPLY, payAmount);
+      const walletBalanceBefore = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      const swapIX = await program.methods
+        .swapCoins({
+          amount: new anchor.BN(amountToSell.toString()),
+          minReceive: new anchor.BN(expectReceive.toString()),
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinA: coinA.coinPda,
+          coinAVault: coinA.coinVaultAta,
+          coinASolVault: coinA.solVaultPda,
+          coinAPayer: coinAAta,
+          coinB: coinB.coinPda,
+          coinBVault: coinB.coinVaultAta,
+          coinBSolVault: coinB.solVaultPda,
+          coinBRecipient: coinBAta,
+          coinBUserState: userStatePda(coinB.coinPda, wallet.publicKey),
+          payer: wallet.publicKey,
+        })
+        .instruction();
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(swapIX), wallet);
+
+      const coinAAtaAfter = await getAccount(anchor.getProvider().connection, coinAAta);
+      const coinBAtaAfter = await getAccount(anchor.getProvider().connection, coinBAta);
+      expect(coinAAtaAfter.amount).to.eq(buyAmount - amountToSell);
+      expect(coinBAtaAfter.amount).to.eq(expectReceive);
+
+      // the sol left in the wallet is at most the transaction fee plus the rounding dust
+      const walletBalanceAfter = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      const spent = walletBalanceBefore - walletBalanceAfter;
+      const dust = Number(receive - payAmount - buy_fee(payAmount, BigInt(cfg.makerFeeRate)));
+      // the wallet also pays the rent of its user state on coin B
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(spent).to.eq(5000 + userStateRent - dust);
+
+      const coinBState = await program.account.coin.fetch(coinB.coinPda);
+      expect(coinBState.accumulateSol.toString()).to.eq(payAmount.toString());
+    });
+  });
+
+  describe("#open_position", () => {
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const userPosition = await openPosition(coinPda);
+      const position = await program.account.userPosition.fetch(userPosition);
+      expect(position.owner.toBase58()).to.eq(wallet.publicKey.toBase58());
+      expect(position.coin.toBase58()).to.eq(coinPda.toBase58());
+      expect(position.tradeCount.toNumber()).to.eq(0);
+    });
+
+    it("should be updated by buys and sells", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const userPosition = await openPosition(coinPda);
+      const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+
+      // buy
+      const buyAmount = BigInt(1e8) * BigInt(1e6);
+      const payWithoutFee = buy(MAX_COIN_SUPPLY, buyAmount);
+      const buyFee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      await program.methods
+        .buy({
+          amount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN((payWithoutFee + buyFee).toString()),
+          expiry: null,
+          allowlistProof: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          coinRecipient: coinRecipientAta,
+          recipient: wallet.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          userPosition: userPosition,
+        })
+        .rpc();
+
+      // sell a quarter
+      const sellAmount = buyAmount / 4n;
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - buyAmount, sellAmount);
+      const sellFee = sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+      await program.methods
+        .sell({ amount: new anchor.BN(sellAmount.toString()), minReceive: new anchor.BN(0), expiry: null })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          coin: coinPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinVault: coinVaultAta,
+          solVault: solVaultPda,
+          solRecipient: wallet.publicKey,
+          coinPayer: coinRecipientAta,
+          userPosition: userPosition,
+        })
+        .rpc();
+
+      const position = await program.account.userPosition.fetch(userPosition);
+      expect(position.coinsBought.toString()).to.eq(buyAmount.toString());
+      expect(position.coinsSold.toString()).to.eq(sellAmount.toString());
+      expect(position.solPaid.toString()).to.eq(payWithoutFee.toString());
+      expect(position.solReceived.toString()).to.eq((receiveWithFee - sellFee).toString());
+      expect(position.feesPaid.toString()).to.eq((buyFee + sellFee).toString());
+      expect(position.tradeCount.toNumber()).to.eq(2);
+      // the quarter sold takes a quarter of the cost basis
+      const cost = payWithoutFee + buyFee;
+      const soldCost = cost / 4n;
+      expect(position.costBasis.toString()).to.eq((cost - soldCost).toString());
+      expect(position.realizedPnl.toString()).to.eq((receiveWithFee - sellFee - soldCost).toString());
+    });
+
+    it("should be opened and updated by create_and_buy", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxCreatorBuyBps: 1000 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, accounts, coinRecipientAta } = createCoinAccounts(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const [userPosition] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(USER_POSITION_SEED), accounts.coin.toBuffer(), wallet.publicKey.toBuffer()],
+        program.programId
+      );
+
+      const payWithoutFee = BigInt(1e9);
+      const buyAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
+      const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+      await program.methods
+        .createAndBuy({
+          create: {
+            name: "Coin name",
+            symbol: "CS",
+            uri: "https://example.org",
+            allowlistRoot: null,
+            allowlistSlots: new anchor.BN(0),
+            curveKind: { constantProduct: {} },
+            decimals: DECIMALS,
+          },
+          buy: {
+            payAmount: new anchor.BN(payWithoutFee.toString()),
+            minReceive: new anchor.BN(buyAmount.toString()),
+            expiry: null,
+            allowlistProof: null,
+            feeIncluded: false,
+          },
+        })
+        .accountsPartial({
+          create: accounts,
+          coinRecipient: coinRecipientAta,
+          userState: userStatePda(accounts.coin, wallet.publicKey),
+          userPosition: userPosition,
+        })
+        .signers([wallet, mintKeypair])
+        .rpc();
+
+      const position = await program.account.userPosition.fetch(userPosition);
+      expect(position.owner.toBase58()).to.eq(wallet.publicKey.toBase58());
+      expect(position.coin.toBase58()).to.eq(accounts.coin.toBase58());
+      expect(position.coinsBought.toString()).to.eq(buyAmount.toString());
+      expect(position.solPaid.toString()).to.eq(payWithoutFee.toString());
+      expect(position.feesPaid.toString()).to.eq(fee.toString());
+      expect(position.costBasis.toString()).to.eq((payWithoutFee + fee).toString());
+      expect(position.tradeCount.toNumber()).to.eq(1);
+    });
+
+    it("should be updated by order fills and DCA slices", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const userPosition = await openPosition(coinPda);
+
+      // fill a buy order
+      const amount = BigInt(1e9);
+      const orderPayAmount = buy_pay_amount(amount, BigInt(cfg.makerFeeRate));
+      const orderQuote = buy_exact_in(MAX_COIN_SUPPLY, orderPayAmount);
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { buy: {} },
+        kind: { limit: {} },
+        amount: amount,
+        triggerAmount: orderQuote,
+        minReceive: orderQuote,
+      });
+      const keeper = await createKeeper(mintKeypair.publicKey);
+      await executeOrder(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        mintKeypair.publicKey,
+        order,
+        keeper,
+        userPosition
+      );
+
+      // execute a slice of a DCA
+      const slicePayAmount = BigInt(1e8);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: slicePayAmount,
+        minReceive: 1n,
+        interval: 3600,
+        deposit: 2n * (slicePayAmount + buy_fee(slicePayAmount, BigInt(cfg.makerFeeRate))),
+      });
+      const sliceQuote = buy_exact_in(MAX_COIN_SUPPLY - orderQuote, slicePayAmount);
+      await executeDca(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        mintKeypair.publicKey,
+        dca,
+        keeper,
+        userPosition
+      );
+
+      const position = await program.account.userPosition.fetch(userPosition);
+      expect(position.coinsBought.toString()).to.eq((orderQuote + sliceQuote).toString());
+      expect(position.solPaid.toString()).to.eq((orderPayAmount + slicePayAmount).toString());
+      expect(position.tradeCount.toNumber()).to.eq(2);
+    });
+
+    it("should be updated by both legs of a swap", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const coinA = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const coinB = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const coinAAta = getAssociatedTokenAddressSync(coinA.mintKeypair.publicKey, wallet.publicKey);
+      const coinBAta = getAssociatedTokenAddressSync(coinB.mintKeypair.publicKey, wallet.publicKey);
+      const buyAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinA.mintKeypair.publicKey,
+        coinA.coinPda,
+        coinA.coinVaultAta,
+        coinA.solVaultPda
+      );
+      await sendAndConfirmTransaction(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            wallet.publicKey,
+            coinBAta,
+            wallet.publicKey,
+            coinB.mintKeypair.publicKey
+          )
+        ),
+        wallet
+      );
+      const coinAUserPosition = await openPosition(coinA.coinPda);
+      const coinBUserPosition = await openPosition(coinB.coinPda);
+
+      const amountToSell = buyAmount / 2n;
+      const totalReceive = sell(MAX_COIN_SUPPLY - buyAmount, amountToSell);
+      const receive = totalReceive - sell_fee(totalReceive, BigInt(cfg.takerFeeRate));
+      const payAmount = buy_pay_amount(receive, BigInt(cfg.makerFeeRate));
+      const expectReceive = buy_exact_in(MAX_COIN_SUPPLY, payAmount);
+      await program.methods
+        .swapCoins({
+          amount: new anchor.BN(amountToSell.toString()),
+          minReceive: new anchor.BN(expectReceive.toString()),
+          expiry: null,
+        })
+        .accountsPartial({
+          config: cfgAcctKeypair.publicKey,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          coinA: coinA.coinPda,
+          coinAVault: coinA.coinVaultAta,
+          coinASolVault: coinA.solVaultPda,
+          coinAPayer: coinAAta,
+          coinAUserPosition: coinAUserPosition,
+          coinB: coinB.coinPda,
+          coinBVault: coinB.coinVaultAta,
+          coinBSolVault: coinB.solVaultPda,
+          coinBRecipient: coinBAta,
+          coinBUserState: userStatePda(coinB.coinPda, wallet.publicKey),
+          coinBUserPosition: coinBUserPosition,
+          payer: wallet.publicKey,
+        })
+        .rpc();
+
+      const positionA = await program.account.userPosition.fetch(coinAUserPosition);
+      expect(positionA.coinsSold.toString()).to.eq(amountToSell.toString());
+      expect(positionA.solReceived.toString()).to.eq(receive.toString());
+      expect(positionA.tradeCount.toNumber()).to.eq(1);
+      const positionB = await program.account.userPosition.fetch(coinBUserPosition);
+      expect(positionB.coinsBought.toString()).to.eq(expectReceive.toString());
+      expect(positionB.solPaid.toString()).to.eq(payAmount.toString());
+      expect(positionB.tradeCount.toNumber()).to.eq(1);
+    });
+
+    it("should failed if the position is not the payer's", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const other = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(other.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const userPosition = await openPosition(coinPda, other);
+
+      try {
+        await program.methods
+          .buy({ amount: new anchor.BN(1e6), maxPay: new anchor.BN(1e9), expiry: null, allowlistProof: null })
+          .accountsPartial({
+            config: cfgAcctKeypair.publicKey,
+            coin: coinPda,
+            coinRecipient: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+            recipient: wallet.publicKey,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            coinVault: coinVaultAta,
+            solVault: solVaultPda,
+            userPosition: userPosition,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6040);
+      }
+    });
+  });
+
+  describe("#close_position", () => {
+    it("should failed if not the owner", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const userPosition = await openPosition(coinPda);
+
+      const other = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .closePosition()
+          .accountsPartial({ userPosition: userPosition, owner: other.publicKey })
+          .signers([other])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6040);
+      }
+    });
+
+    it("should succeed and refund the rent", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const userPosition = await openPosition(coinPda);
+
+      const positionInfo = await anchor.getProvider().connection.getAccountInfo(userPosition);
+      const closeIX = await program.methods
+        .closePosition()
+        .accountsPartial({ userPosition: userPosition, owner: wallet.publicKey })
+        .instruction();
+      const walletBalanceBefore = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(closeIX), wallet);
+
+      const walletBalanceAfter = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      expect(walletBalanceAfter - walletBalanceBefore).to.eq(positionInfo.lamports - 5000);
+      expect(await anchor.getProvider().connection.getAccountInfo(userPosition)).to.be.null;
+    });
+  });
+
+  describe("#place_order", () => {
+    it("should failed if amount is 0", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      try {
+        await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 0, {
+          side: { buy: {} },
+          kind: { limit: {} },
+          amount: 0n,
+          triggerAmount: 0n,
+          minReceive: 0n,
+        });
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6026);
+      }
+    });
+
+    it("should succeed to place a buy order", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ orderKeeperReward: new anchor.BN(1e6) }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+
+      const amount = BigInt(1e9);
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { buy: {} },
+        kind: { limit: {} },
+        amount: amount,
+        triggerAmount: 1n,
+        minReceive: 1n,
+      });
+
+      const orderState = await program.account.order.fetch(order);
+      expect(orderState.owner.toBase58()).to.eq(wallet.publicKey.toBase58());
+      expect(orderState.coin.toBase58()).to.eq(coinPda.toBase58());
+      expect(orderState.amount.toString()).to.eq(amount.toString());
+      expect(orderState.keeperReward.toNumber()).to.eq(1e6);
+
+      const orderInfo = await anchor.getProvider().connection.getAccountInfo(order);
+      const rent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(orderInfo.data.length);
+      expect(orderInfo.lamports).to.eq(rent + Number(amount) + 1e6);
+    });
+
+    it("should succeed to place a sell order", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { sell: {} },
+        kind: { limit: {} },
+        amount: coinAmount,
+        triggerAmount: 1n,
+        minReceive: 1n,
+      });
+
+      const orderCoinVault = await getAccount(
+        anchor.getProvider().connection,
+        getAssociatedTokenAddressSync(mintKeypair.publicKey, order, true)
+      );
+      expect(orderCoinVault.amount).to.eq(coinAmount);
+      const walletCoinAccount = await getAccount(
+        anchor.getProvider().connection,
+        getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey)
+      );
+      expect(walletCoinAccount.amount).to.eq(0n);
+    });
+  });
+
+  describe("#cancel_order", () => {
+    it("should failed if not the owner", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { buy: {} },
+        kind: { limit: {} },
+        amount: BigInt(1e9),
+        triggerAmount: 1n,
+        minReceive: 1n,
+      });
+
+      const other = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .cancelOrder()
+          .accountsPartial({
+            coin: coinPda,
+            order: order,
+            orderCoinVault: getAssociatedTokenAddressSync(mintKeypair.publicKey, order, true),
+            ownerCoinAccount: getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey),
+            owner: other.publicKey,
+          })
+          .signers([wallet, other])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6027);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { sell: {} },
+        kind: { stop: {} },
+        amount: coinAmount,
+        triggerAmount: 1n,
+        minReceive: 0n,
+      });
+
+      const orderCoinVault = getAssociatedTokenAddressSync(mintKeypair.publicKey, order, true);
+      const walletCoinAccount = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+      await program.methods
+        .cancelOrder()
+        .accountsPartial({
+          coin: coinPda,
+          order: order,
+          orderCoinVault: orderCoinVault,
+          ownerCoinAccount: walletCoinAccount,
+          owner: wallet.publicKey,
+        })
+        .rpc();
+
+      const walletCoinAccountAfter = await getAccount(anchor.getProvider().connection, walletCoinAccount);
+      expect(walletCoinAccountAfter.amount).to.eq(coinAmount);
+      expect(await anchor.getProvider().connection.getAccountInfo(order)).to.be.null;
+      expect(await anchor.getProvider().connection.getAccountInfo(orderCoinVault)).to.be.null;
+    });
+  });
+
+  describe("#execute_order", () => {
+    it("should failed if not triggered", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const amount = BigInt(1e9);
+      const quote = buy_exact_in(MAX_COIN_SUPPLY, buy_pay_amount(amount, BigInt(cfg.makerFeeRate)));
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { buy: {} },
+        kind: { limit: {} },
+        amount: amount,
+        triggerAmount: quote + 1n,
+        minReceive: quote + 1n,
+      });
+
+      const keeper = await createKeeper(mintKeypair.publicKey);
+      try {
+        await executeOrder(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          coinPda,
+          coinVaultAta,
+          solVaultPda,
+          mintKeypair.publicKey,
+          order,
+          keeper
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6028).toString(16))).to.be.true;
+      }
+    });
+
+    it("should failed if a sell order is filled during the sell cooldown", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ sellCooldownSlots: new anchor.BN(1000), cooldownSellTaxRate: 0 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { sell: {} },
+        kind: { stop: {} },
+        amount: coinAmount,
+        triggerAmount: 1n,
+        minReceive: 0n,
+      });
+
+      const keeper = await createKeeper(mintKeypair.publicKey);
+      try {
+        await executeOrder(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          coinPda,
+          coinVaultAta,
+          solVaultPda,
+          mintKeypair.publicKey,
+          order,
+          keeper
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6042).toString(16))).to.be.true;
+      }
+    });
+
+    it("should succeed to fill a limit buy order", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const keeperReward = 1e6;
+      await program.methods
+        .updateConfig(updateConfigArgs({ orderKeeperReward: new anchor.BN(keeperReward) }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const amount = BigInt(1e9);
+      const payAmount = buy_pay_amount(amount, BigInt(cfg.makerFeeRate));
+      const quote = buy_exact_in(MAX_COIN_SUPPLY, payAmount);
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { buy: {} },
+        kind: { limit: {} },
+        amount: amount,
+        triggerAmount: quote,
+        minReceive: quote,
+      });
+
+      const keeper = await createKeeper(mintKeypair.publicKey);
+      const keeperBalanceBefore = await anchor.getProvider().connection.getBalance(keeper.publicKey);
+      await executeOrder(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        mintKeypair.publicKey,
+        order,
+        keeper
+      );
+
+      // the keeper pays the transaction fee and the rent of the user state of the owner
+      const keeperBalanceAfter = await anchor.getProvider().connection.getBalance(keeper.publicKey);
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(keeperBalanceAfter - keeperBalanceBefore).to.eq(keeperReward - 5000 - userStateRent);
+
+      const userState = await program.account.userState.fetch(userStatePda(coinPda, wallet.publicKey));
+      expect(userState.bought.toString()).to.eq(quote.toString());
+
+      const walletCoinAccount = await getAccount(
+        anchor.getProvider().connection,
+        getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey)
+      );
+      expect(walletCoinAccount.amount).to.eq(quote);
+      const { accumulateSol } = await program.account.coin.fetch(coinPda);
+      expect(accumulateSol.toString()).to.eq(payAmount.toString());
+      expect(await anchor.getProvider().connection.getAccountInfo(order)).to.be.null;
+    });
+
+    it("should succeed to fill a stop sell order", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const coinAmount = await buyForWallet(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        mintKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda
+      );
+      const receiveWithFee = sell(MAX_COIN_SUPPLY - coinAmount, coinAmount);
+      const quote = receiveWithFee - sell_fee(receiveWithFee, BigInt(cfg.takerFeeRate));
+      const order = await placeOrder(cfgAcctKeypair.publicKey, coinPda, mintKeypair.publicKey, 1, {
+        side: { sell: {} },
+        kind: { stop: {} },
+        amount: coinAmount,
+        triggerAmount: quote,
+        minReceive: quote,
+      });
+
+      const orderInfo = await anchor.getProvider().connection.getAccountInfo(order);
+      const orderCoinVault = getAssociatedTokenAddressSync(mintKeypair.publicKey, order, true);
+      const orderCoinVaultInfo = await anchor.getProvider().connection.getAccountInfo(orderCoinVault);
+      const walletBalanceBefore = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      const keeper = await createKeeper(mintKeypair.publicKey);
+      await executeOrder(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        mintKeypair.publicKey,
+        order,
+        keeper
+      );
+
+      // the owner receives the sol and the rent of the order accounts
+      const walletBalanceAfter = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      expect(walletBalanceAfter - walletBalanceBefore).to.eq(
+        Number(quote) + orderInfo.lamports + orderCoinVaultInfo.lamports
+      );
+      const keeperCoinAccount = await getAccount(
+        anchor.getProvider().connection,
+        getAssociatedTokenAddressSync(mintKeypair.publicKey, keeper.publicKey)
+      );
+      expect(keeperCoinAccount.amount).to.eq(0n);
+      const { remainingCoinSupply } = await program.account.coin.fetch(coinPda);
+      expect(remainingCoinSupply.toString()).to.eq(MAX_COIN_SUPPLY.toString());
+      expect(await anchor.getProvider().connection.getAccountInfo(order)).to.be.null;
+      expect(await anchor.getProvider().connection.getAccountInfo(orderCoinVault)).to.be.null;
+    });
+  });
+
+  describe("#create_dca", () => {
+    it("should failed if pay amount is 0", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      try {
+        await createDca(coinPda, mintKeypair.publicKey, 1, {
+          payAmount: 0n,
+          minReceive: 0n,
+          interval: 3600,
+          deposit: BigInt(1e9),
+        });
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6029);
+      }
+    });
+
+    it("should failed if interval is 0", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      try {
+        await createDca(coinPda, mintKeypair.publicKey, 1, {
+          payAmount: BigInt(1e8),
+          minReceive: 0n,
+          interval: 0,
+          deposit: BigInt(1e9),
+        });
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6029);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const deposit = BigInt(1e9);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: BigInt(1e8),
+        minReceive: 1n,
+        interval: 3600,
+        deposit: deposit,
+      });
+
+      const dcaState = await program.account.dca.fetch(dca);
+      expect(dcaState.owner.toBase58()).to.eq(wallet.publicKey.toBase58());
+      expect(dcaState.payAmount.toNumber()).to.eq(1e8);
+      expect(dcaState.escrow.toString()).to.eq(deposit.toString());
+
+      const dcaInfo = await anchor.getProvider().connection.getAccountInfo(dca);
+      const rent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(dcaInfo.data.length);
+      expect(dcaInfo.lamports).to.eq(rent + Number(deposit));
+    });
+  });
+
+  describe("#execute_dca", () => {
+    it("should failed if the interval has not elapsed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const payAmount = BigInt(1e8);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: payAmount,
+        minReceive: 1n,
+        interval: 3600,
+        deposit: 3n * (payAmount + buy_fee(payAmount, BigInt(cfg.makerFeeRate))),
+      });
+
+      const cranker = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(cranker.publicKey, 1e9);
+      await confirmTransaction(tx);
+      await executeDca(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        mintKeypair.publicKey,
+        dca,
+        cranker
+      );
+      try {
+        await executeDca(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          coinPda,
+          coinVaultAta,
+          solVaultPda,
+          mintKeypair.publicKey,
+          dca,
+          cranker
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6030).toString(16))).to.be.true;
+      }
+    });
+
+    it("should failed if the slice receives less than the minimum", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const payAmount = BigInt(1e8);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: payAmount,
+        minReceive: buy_exact_in(MAX_COIN_SUPPLY, payAmount) + 1n,
+        interval: 1,
+        deposit: payAmount + buy_fee(payAmount, BigInt(cfg.makerFeeRate)),
+      });
+
+      const cranker = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(cranker.publicKey, 1e9);
+      await confirmTransaction(tx);
+      try {
+        await executeDca(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          coinPda,
+          coinVaultAta,
+          solVaultPda,
+          mintKeypair.publicKey,
+          dca,
+          cranker
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6011).toString(16))).to.be.true;
+      }
+    });
+
+    it("should succeed and close once the escrow is spent", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const payAmount = BigInt(1e8);
+      const slice = payAmount + buy_fee(payAmount, BigInt(cfg.makerFeeRate));
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: payAmount,
+        minReceive: 1n,
+        interval: 1,
+        deposit: 2n * slice,
+      });
+
+      const cranker = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(cranker.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const crankerBalanceBefore = await anchor.getProvider().connection.getBalance(cranker.publicKey);
+      for (let i = 0; i < 2; i++) {
+        if (i > 0) {
+          // wait for the interval to elapse
+          await new Promise((resolve) => setTimeout(resolve, 2000));
+        }
+        await executeDca(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          coinPda,
+          coinVaultAta,
+          solVaultPda,
+          mintKeypair.publicKey,
+          dca,
+          cranker
+        );
+      }
+
+      // the cranker only pays the transaction fees and the rent of the user state of the owner
+      const crankerBalanceAfter = await anchor.getProvider().connection.getBalance(cranker.publicKey);
+      const userStateRent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(USER_STATE_LEN);
+      expect(crankerBalanceBefore - crankerBalanceAfter).to.eq(2 * 5000 + userStateRent);
+
+      const firstAmount = buy_exact_in(MAX_COIN_SUPPLY, payAmount);
+      const secondAmount = buy_exact_in(MAX_COIN_SUPPLY - firstAmount, payAmount);
+      const walletCoinAccount = await getAccount(
+        anchor.getProvider().connection,
+        getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey)
+      );
+      expect(walletCoinAccount.amount).to.eq(firstAmount + secondAmount);
+      expect(await anchor.getProvider().connection.getAccountInfo(dca)).to.be.null;
+    });
+
+    it("should failed if wallet buy limit exceeded", async () => {
+      const { cfgAcctKeypair, authorityKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      await program.methods
+        .updateConfig(updateConfigArgs({ maxWalletBuyBps: 100 }))
+        .accountsPartial({ config: cfgAcctKeypair.publicKey, authority: authorityKeypair.publicKey })
+        .signers([wallet, authorityKeypair])
+        .rpc();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      // 1 SOL buys more than 1% of the supply
+      const payAmount = BigInt(1e9);
+      expect(buy_exact_in(MAX_COIN_SUPPLY, payAmount) > MAX_COIN_SUPPLY / 100n).to.be.true;
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: payAmount,
+        minReceive: 1n,
+        interval: 1,
+        deposit: payAmount + buy_fee(payAmount, BigInt(cfg.makerFeeRate)),
+      });
+
+      const cranker = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(cranker.publicKey, 1e9);
+      await confirmTransaction(tx);
+      try {
+        await executeDca(
+          cfgAcctKeypair.publicKey,
+          feeRecipientKeypair.publicKey,
+          coinPda,
+          coinVaultAta,
+          solVaultPda,
+          mintKeypair.publicKey,
+          dca,
+          cranker
+        );
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        const sendTxError = e as anchor.web3.SendTransactionError;
+        expect(sendTxError.message.includes(BigInt(6034).toString(16))).to.be.true;
+      }
+    });
+
+    it("should refund the escrow once the coin graduates", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair, cfg } = await initializeConfig();
+      const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey
+      );
+      const deposit = BigInt(1e9);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: BigInt(1e8),
+        minReceive: 1n,
+        interval: 1,
+        deposit: deposit,
+      });
+      await buyAllCoins(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey, mintKeypair.publicKey, cfg);
+
+      const dcaInfo = await anchor.getProvider().connection.getAccountInfo(dca);
+      const walletBalanceBefore = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      const cranker = anchor.web3.Keypair.generate();
+      const tx = await anchor.getProvider().connection.requestAirdrop(cranker.publicKey, 1e9);
+      await confirmTransaction(tx);
+      await executeDca(
+        cfgAcctKeypair.publicKey,
+        feeRecipientKeypair.publicKey,
+        coinPda,
+        coinVaultAta,
+        solVaultPda,
+        mintKeypair.publicKey,
+        dca,
+        cranker
+      );
+
+      const walletBalanceAfter = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      expect(walletBalanceAfter - walletBalanceBefore).to.eq(dcaInfo.lamports);
+      expect(await anchor.getProvider().connection.getAccountInfo(dca)).to.be.null;
+    });
+  });
+
+  describe("#cancel_dca", () => {
+    it("should failed if not the owner", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: BigInt(1e8),
+        minReceive: 1n,
+        interval: 3600,
+        deposit: BigInt(1e9),
+      });
+
+      const other = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .cancelDca()
+          .accountsPartial({ dca: dca, owner: other.publicKey })
+          .signers([wallet, other])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.number).to.be.eq(6031);
+      }
+    });
+
+    it("should succeed", async () => {
+      const { cfgAcctKeypair, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, coinPda } = await createCoin(cfgAcctKeypair.publicKey, feeRecipientKeypair.publicKey);
+      const dca = await createDca(coinPda, mintKeypair.publicKey, 1, {
+        payAmount: BigInt(1e8),
+        minReceive: 1n,
+        interval: 3600,
+        deposit: BigInt(1e9),
+      });
+
+      const dcaInfo = await anchor.getProvider().connection.getAccountInfo(dca);
+      const cancelIX = await program.methods
+        .cancelDca()
+        .accountsPartial({ dca: dca, owner: wallet.publicKey })
+        .instruction();
+      const walletBalanceBefore = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      await sendAndConfirmTransaction(new anchor.web3.Transaction().add(cancelIX), wallet);
+
+      const walletBalanceAfter = await anchor.getProvider().connection.getBalance(wallet.publicKey);
+      expect(walletBalanceAfter - walletBalanceBefore).to.eq(dcaInfo.lamports - 5000);
+      expect(await anchor.getProvider().connection.getAccountInfo(dca)).to.be.null;
+    });
+  });
+
+  async function initializeConfig(
+    createCoinFee: anchor.BN = new anchor.BN(1e9),
+    makerFeeRate: number = Number((FEE_RATE_BASIS_POINT * 1n) / 100n),
+    takerFeeRate: number = Number((FEE_RATE_BASIS_POINT * 1n) / 100n),
+    curveParams = CURVE_PARAMS
+  ) {
+    const cfgAcctKeypair = anchor.web3.Keypair.generate();
+    const authorityKeypair = anchor.web3.Keypair.generate();
+    const feeRecipientKeypair = anchor.web3.Keypair.generate();
+    const migrationKeypair = anchor.web3.Keypair.generate();
+    let cfg = {
+      authority: authorityKeypair.publicKey,
+      feeRecipient: feeRecipientKeypair.publicKey,
+      migrationAuthority: migrationKeypair.publicKey,
+      createCoinFee: createCoinFee,
+      makerFeeRate: makerFeeRate,
       takerFeeRate: takerFeeRate,
+      curveParams: curveParams,
     };
     await program.methods
       .initializeConfig(cfg)
@@ -2408,12 +6054,42 @@ describe("soc-pump-fun", () => {
     };
   }
 
+  function updateConfigArgs(
+    args: Partial<{
+      maxCreatorBuyBps: number;
+      orderKeeperReward: anchor.BN;
+      maxWalletBuyBps: number;
+      launchTaxRate: number;
+      launchTaxSlots: anchor.BN;
+      sellCooldownSlots: anchor.BN;
+      cooldownSellTaxRate: number;
+      maxPriceImpactBps: number;
+      curveParams: typeof CURVE_PARAMS;
+    }>
+  ) {
+    return {
+      maxCreatorBuyBps: null,
+      orderKeeperReward: null,
+      maxWalletBuyBps: null,
+      launchTaxRate: null,
+      launchTaxSlots: null,
+      sellCooldownSlots: null,
+      cooldownSellTaxRate: null,
+      maxPriceImpactBps: null,
+      curveParams: null,
+      ...args,
+    };
+  }
+
   async function createCoin(
     cfgAcct: anchor.web3.PublicKey,
     feeRecipient: anchor.web3.PublicKey,
     name: string = "Coin name",
     symbol: string = "CS",
-    uri: string = "https://example.org"
+    uri: string = "https://example.org",
+    allowlist: { root: Buffer; slots: number | anchor.BN } | null = null,
+    curveKind: { constantProduct: {} } | { linear: {} } | { exponential: {} } = { constantProduct: {} },
+    decimals: number = DECIMALS
   ) {
     const mintKeypair = anchor.web3.Keypair.generate();
     const [metadataPda, metadataBump] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -2424,6 +6100,10 @@ describe("soc-pump-fun", () => {
       name: name,
       symbol: symbol,
       uri: uri,
+      allowlistRoot: allowlist ? Array.from(allowlist.root) : null,
+      allowlistSlots: new anchor.BN(allowlist ? allowlist.slots : 0),
+      curveKind: curveKind,
+      decimals: decimals,
     };
     const [coinPda, coinBump] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
@@ -2451,6 +6131,339 @@ describe("soc-pump-fun", () => {
     return { mintKeypair, metadataPda, coinPda, coinVaultAta, solVaultPda };
   }
 
+  async function buyAllCoins(
+    cfgAcct: anchor.web3.PublicKey,
+    feeRecipient: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    cfg: { makerFeeRate: number }
+  ) {
+    const payer = anchor.web3.Keypair.generate();
+    const tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9 * 100);
+    await confirmTransaction(tx);
+
+    const coinRecipientAta = getAssociatedTokenAddressSync(mint, payer.publicKey);
+    const createAtaIX = createAssociatedTokenAccountInstruction(
+      wallet.publicKey,
+      coinRecipientAta,
+      payer.publicKey,
+      mint
+    );
+    const payWithoutFee = buy(MAX_COIN_SUPPLY, SELLABLE_COINS);
+    const fee = buy_fee(payWithoutFee, BigInt(cfg.makerFeeRate));
+    const [coinPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(COIN_SEED), mint.toBuffer()],
+      program.programId
+    );
+    const buyIX = await program.methods
+      .buy({
+        amount: new anchor.BN(SELLABLE_COINS.toString()),
+        maxPay: new anchor.BN((payWithoutFee + fee).toString()),
+        expiry: null,
+        allowlistProof: null,
+      })
+      .accountsPartial({
+        config: cfgAcct,
+        coin: coinPda,
+        coinRecipient: coinRecipientAta,
+        recipient: payer.publicKey,
+        feeRecipient: feeRecipient,
+        coinVault: getAssociatedTokenAddressSync(mint, coinPda, true),
+        solVault: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from(SOL_VAULT_SEED), mint.toBuffer()],
+          program.programId
+        )[0],
+        payer: payer.publicKey,
+      })
+      .instruction();
+    await sendAndConfirmTransaction(new anchor.web3.Transaction().add(createAtaIX, buyIX), wallet, payer);
+
+    return { payer, coinRecipientAta };
+  }
+
+  async function createCoinWithPool() {
+    const { cfgAcctKeypair, feeRecipientKeypair, migrationKeypair, cfg } = await initializeConfig();
+    const { mintKeypair, coinPda, coinVaultAta, solVaultPda } = await createCoin(
+      cfgAcctKeypair.publicKey,
+      feeRecipientKeypair.publicKey
+    );
+    const { payer, coinRecipientAta } = await buyAllCoins(
+      cfgAcctKeypair.publicKey,
+      feeRecipientKeypair.publicKey,
+      mintKeypair.publicKey,
+      cfg
+    );
+    const tx = await anchor.getProvider().connection.requestAirdrop(migrationKeypair.publicKey, 1e9);
+    await confirmTransaction(tx);
+    await program.methods
+      .createPool()
+      .accountsPartial({
+        config: cfgAcctKeypair.publicKey,
+        coin: coinPda,
+        pool: poolPda(mintKeypair.publicKey),
+        solVault: solVaultPda,
+        migrationAuthority: migrationKeypair.publicKey,
+      })
+      .signers([migrationKeypair])
+      .rpc();
+
+    return {
+      cfgAcctKeypair,
+      feeRecipientKeypair,
+      cfg,
+      mintKeypair,
+      coinPda,
+      coinVaultAta,
+      solVaultPda,
+      payer,
+      coinAta: coinRecipientAta,
+    };
+  }
+
+  function poolPda(mint: anchor.web3.PublicKey) {
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(POOL_SEED), mint.toBuffer()],
+      program.programId
+    );
+    return pda;
+  }
+
+  async function openPosition(coin: anchor.web3.PublicKey, owner: anchor.web3.Keypair = wallet) {
+    const [userPosition] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(USER_POSITION_SEED), coin.toBuffer(), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .openPosition()
+      .accountsPartial({ coin: coin, userPosition: userPosition, owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    return userPosition;
+  }
+
+  function orderPda(coin: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, id: number) {
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(ORDER_SEED), coin.toBuffer(), owner.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    return pda;
+  }
+
+  async function placeOrder(
+    cfgAcct: anchor.web3.PublicKey,
+    coin: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    id: number,
+    args: {
+      side: { buy: {} } | { sell: {} };
+      kind: { limit: {} } | { stop: {} };
+      amount: bigint;
+      triggerAmount: bigint;
+      minReceive: bigint;
+    }
+  ) {
+    const order = orderPda(coin, wallet.publicKey, id);
+    await program.methods
+      .placeOrder({
+        id: new anchor.BN(id),
+        side: args.side,
+        kind: args.kind,
+        amount: new anchor.BN(args.amount.toString()),
+        triggerAmount: new anchor.BN(args.triggerAmount.toString()),
+        minReceive: new anchor.BN(args.minReceive.toString()),
+      })
+      .accountsPartial({
+        config: cfgAcct,
+        coin: coin,
+        coinMint: mint,
+        order: order,
+        orderCoinVault: getAssociatedTokenAddressSync(mint, order, true),
+        ownerCoinAccount: getAssociatedTokenAddressSync(mint, wallet.publicKey),
+        owner: wallet.publicKey,
+      })
+      .rpc();
+    return order;
+  }
+
+  async function buyForWallet(
+    cfgAcct: anchor.web3.PublicKey,
+    feeRecipient: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    coin: anchor.web3.PublicKey,
+    coinVault: anchor.web3.PublicKey,
+    solVault: anchor.web3.PublicKey
+  ) {
+    const payWithoutFee = BigInt(1e9);
+    const coinAmount = buy_exact_in(MAX_COIN_SUPPLY, payWithoutFee);
+    await program.methods
+      .buyExactIn({
+        payAmount: new anchor.BN(payWithoutFee.toString()),
+        minReceive: new anchor.BN(coinAmount.toString()),
+        expiry: null,
+        allowlistProof: null,
+        feeIncluded: false,
+      })
+      .accountsPartial({
+        config: cfgAcct,
+        coin: coin,
+        coinRecipient: getAssociatedTokenAddressSync(mint, wallet.publicKey),
+        recipient: wallet.publicKey,
+        feeRecipient: feeRecipient,
+        coinVault: coinVault,
+        solVault: solVault,
+      })
+      .rpc();
+    return coinAmount;
+  }
+
+  function allowlistLeaf(wallet: anchor.web3.PublicKey, allocation: bigint) {
+    const allocationBuffer = Buffer.alloc(8);
+    allocationBuffer.writeBigUInt64LE(allocation);
+    return createHash("sha256").update(wallet.toBuffer()).update(allocationBuffer).digest();
+  }
+
+  function allowlistNode(left: Buffer, right: Buffer) {
+    const [first, second] = Buffer.compare(left, right) <= 0 ? [left, right] : [right, left];
+    return createHash("sha256").update(first).update(second).digest();
+  }
+
+  async function createKeeper(mint: anchor.web3.PublicKey) {
+    const keeper = anchor.web3.Keypair.generate();
+    const tx = await anchor.getProvider().connection.requestAirdrop(keeper.publicKey, 1e9 * 10);
+    await confirmTransaction(tx);
+    await getOrCreateAssociatedTokenAccount(anchor.getProvider().connection, keeper, mint, keeper.publicKey);
+    return keeper;
+  }
+
+  async function executeOrder(
+    cfgAcct: anchor.web3.PublicKey,
+    feeRecipient: anchor.web3.PublicKey,
+    coin: anchor.web3.PublicKey,
+    coinVault: anchor.web3.PublicKey,
+    solVault: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    order: anchor.web3.PublicKey,
+    keeper: anchor.web3.Keypair,
+    userPosition: anchor.web3.PublicKey | null = null
+  ) {
+    const executeIX = await program.methods
+      .executeOrder()
+      .accountsPartial({
+        config: cfgAcct,
+        coin: coin,
+        feeRecipient: feeRecipient,
+        coinVault: coinVault,
+        solVault: solVault,
+        order: order,
+        orderCoinVault: getAssociatedTokenAddressSync(mint, order, true),
+        owner: wallet.publicKey,
+        ownerCoinAccount: getAssociatedTokenAddressSync(mint, wallet.publicKey),
+        keeper: keeper.publicKey,
+        keeperCoinAccount: getAssociatedTokenAddressSync(mint, keeper.publicKey),
+        userPosition: userPosition,
+      })
+      .instruction();
+    return sendAndConfirmTransaction(new anchor.web3.Transaction().add(executeIX), keeper);
+  }
+
+  function userStatePda(coin: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) {
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(USER_STATE_SEED), coin.toBuffer(), owner.toBuffer()],
+      program.programId
+    );
+    return pda;
+  }
+
+  function dcaPda(coin: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, id: number) {
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(DCA_SEED), coin.toBuffer(), owner.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    return pda;
+  }
+
+  async function createDca(
+    coin: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    id: number,
+    args: { payAmount: bigint; minReceive: bigint; interval: number; deposit: bigint }
+  ) {
+    const dca = dcaPda(coin, wallet.publicKey, id);
+    await program.methods
+      .createDca({
+        id: new anchor.BN(id),
+        payAmount: new anchor.BN(args.payAmount.toString()),
+        minReceive: new anchor.BN(args.minReceive.toString()),
+        interval: new anchor.BN(args.interval),
+        deposit: new anchor.BN(args.deposit.toString()),
+      })
+      .accountsPartial({
+        coin: coin,
+        coinMint: mint,
+        dca: dca,
+        ownerCoinAccount: getAssociatedTokenAddressSync(mint, wallet.publicKey),
+        owner: wallet.publicKey,
+      })
+      .rpc();
+    return dca;
+  }
+
+  async function executeDca(
+    cfgAcct: anchor.web3.PublicKey,
+    feeRecipient: anchor.web3.PublicKey,
+    coin: anchor.web3.PublicKey,
+    coinVault: anchor.web3.PublicKey,
+    solVault: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    dca: anchor.web3.PublicKey,
+    cranker: anchor.web3.Keypair,
+    userPosition: anchor.web3.PublicKey | null = null
+  ) {
+    const executeIX = await program.methods
+      .executeDca()
+      .accountsPartial({
+        config: cfgAcct,
+        coin: coin,
+        feeRecipient: feeRecipient,
+        coinVault: coinVault,
+        solVault: solVault,
+        dca: dca,
+        owner: wallet.publicKey,
+        ownerCoinAccount: getAssociatedTokenAddressSync(mint, wallet.publicKey),
+        cranker: cranker.publicKey,
+        userPosition: userPosition,
+      })
+      .instruction();
+    return sendAndConfirmTransaction(new anchor.web3.Transaction().add(executeIX), cranker);
+  }
+
+  function createCoinAccounts(cfgAcct: anchor.web3.PublicKey, feeRecipient: anchor.web3.PublicKey) {
+    const mintKeypair = anchor.web3.Keypair.generate();
+    const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(METADATA_SEED), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mintKeypair.publicKey.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const [coinPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(COIN_SEED), mintKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+    const [solVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(SOL_VAULT_SEED), mintKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+    const accounts = {
+      config: cfgAcct,
+      coin: coinPda,
+      coinMint: mintKeypair.publicKey,
+      coinVault: getAssociatedTokenAddressSync(mintKeypair.publicKey, coinPda, true),
+      solVault: solVaultPda,
+      tokenMetadata: metadataPda,
+      feeRecipient: feeRecipient,
+      payer: wallet.publicKey,
+    };
+    const coinRecipientAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, wallet.publicKey);
+    return { mintKeypair, accounts, coinRecipientAta };
+  }
+
   async function sendAndConfirmTransaction(tx: anchor.web3.Transaction, ...signers: Array<anchor.web3.Keypair>) {
     const { lastValidBlockHeight, blockhash } = await anchor.getProvider().connection.getLatestBlockhash();
     tx.lastValidBlockHeight = lastValidBlockHeight;